@@ -25,17 +25,20 @@ SUPER='Mod4+'
 main() {
   case "${1}"
     in api)
-      if [ -z "${2}" ]; then
-        shift 2
-        process_keyspace '' "$@"
-      else
-        domainify_title "${2}"
-        printf '\nmode "%s" {\n' "${title}"
-        shift 2
-
-        process_keyspace '  ' "$@"
-        printf '}\n'
-      fi
+      shift 1
+      process_title_and_keyspace "$@"
+
+    ;; batch)
+      # 'begin <title> [<chord> state|run <arg>]... end' for every keyspace
+      shift 1
+      while [ "$#" -gt 0 ]; do
+        [ "${1}" = "begin" ] || die FATAL 1 "Expected 'begin' but got: ${1}"
+        shift 1
+        process_title_and_keyspace "$@" # Sets ${consumed}
+        shift "${consumed}"
+        [ "${1}" = "end" ] || die FATAL 1 "Expected 'end' but got: ${1}"
+        shift 1
+      done
 
     ;; bench)
       counter="0"
@@ -47,7 +50,7 @@ main() {
       done
     ;; help) show_help; exit 0
     ;; *)
-      cargo run ./config.txt keyspace-list "./${NAME}" api
+      cargo run ./config.txt --batch keyspace-list "./${NAME}" batch
   esac
 }
 
@@ -57,11 +60,29 @@ domainify_title() {
   title="${hotkey}"
 }
 
+# Sets ${consumed} to the number of arguments used, so that a 'batch' can
+# continue after the 'end' of this keyspace
+process_title_and_keyspace() {
+  if [ -z "${1}" ]; then
+    shift 1
+    process_keyspace '' "$@"
+  else
+    domainify_title "${1}"
+    printf '\nmode "%s" {\n' "${title}"
+    shift 1
+
+    process_keyspace '  ' "$@"
+    printf '}\n'
+  fi
+  consumed="$(( consumed + 1 ))"
+}
+
 process_keyspace() {
-  # Sets ${hotkey}
+  # Sets ${hotkey} and ${consumed}
   _padding="${1}"
   shift 1
-  while [ "$#" -gt 0 ]; do
+  consumed="0"
+  while [ "$#" -gt 0 ] && [ "${1}" != "end" ]; do
     if [ "${2}" = "state" ]; then
       domainify_title "${3}" # Sets ${hotkey} and ${title}
       # Sets ${hotkey}
@@ -74,6 +95,7 @@ process_keyspace() {
         "${_padding}" "${hotkey}" "${3}"
     fi
     shift 3
+    consumed="$(( consumed + 3 ))"
   done
 }
 
@@ -0,0 +1,158 @@
+// AwesomeWM, as a Lua module that returns the root keys for 'root.keys()'
+//
+// Awesome has no chains, so every other keyspace is an 'awful.keygrabber'
+// that is started by a 'SetState' and stopped after an action or Escape
+
+use super::{identifier, join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Awesome<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+const PRELUDE: &str = r#"local awful = require("awful")
+local gears = require("gears")
+
+local modes = {}
+
+-- 'mode' is the keygrabber to stop, nil for the root keys
+local function spawn(mode, command)
+    return function()
+        if mode then modes[mode]:stop() end
+        if command ~= "" then awful.spawn.with_shell(command) end
+    end
+end
+
+local function enter(mode, next_mode)
+    return function()
+        if mode then modes[mode]:stop() end
+        modes[next_mode]:start()
+    end
+end
+"#;
+
+impl fmt::Display for Awesome<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(PRELUDE)?;
+        let (root, rest) = match self.0.split_first() {
+            Some(split) => split,
+            None => return writeln!(f, "\nreturn {{}}"),
+        };
+
+        for keyspace in rest {
+            let mode = quote_string(&identifier(keyspace.title.0), '"', ControlEscape::Decimal);
+            writeln!(f, "\nmodes[{}] = awful.keygrabber {{", mode)?;
+            writeln!(f, "    stop_key = \"Escape\",")?;
+            writeln!(f, "    keybindings = {{")?;
+            for (chord, action) in &keyspace.list {
+                writeln!(
+                    f,
+                    "        {{ {}, {} }},",
+                    AwesomeChord(chord),
+                    callback(&mode, action)
+                )?;
+            }
+            writeln!(f, "    }},")?;
+            writeln!(f, "}}")?;
+        }
+
+        writeln!(f, "\nreturn gears.table.join(")?;
+        let last = root.list.len().saturating_sub(1);
+        for (i, (chord, action)) in root.list.iter().enumerate() {
+            writeln!(
+                f,
+                "    awful.key({}, {}){}",
+                AwesomeChord(chord),
+                callback("nil", action),
+                if i == last { "" } else { "," }
+            )?;
+        }
+        writeln!(f, ")")
+    }
+}
+
+// 'mode' is already a Lua expression
+fn callback(mode: &str, action: &KeyspaceAction) -> String {
+    match action {
+        KeyspaceAction::SetState(chord_list) => {
+            format!(
+                "enter({}, {})",
+                mode,
+                quote_string(&identifier(chord_list), '"', ControlEscape::Decimal)
+            )
+        }
+        KeyspaceAction::Action(action) => {
+            format!(
+                "spawn({}, {})",
+                mode,
+                quote_string(&join_action(action), '"', ControlEscape::Decimal)
+            )
+        }
+    }
+}
+
+// The '{ modifiers }, key' part of 'awful.key'
+struct AwesomeChord<'c>(&'c Chord);
+
+impl fmt::Display for AwesomeChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = self
+            .0
+            .modifier_list()
+            .map(|modifier| match modifier {
+                Mod::Shift => "\"Shift\"",
+                Mod::Super => "\"Mod4\"",
+                Mod::Ctrl => "\"Control\"",
+                Mod::Alt => "\"Mod1\"",
+            })
+            .collect::<Vec<_>>();
+        let key = match self.0.key {
+            Key::Space => "space",
+            Key::Comma => "comma",
+            ref key => key.as_str(),
+        };
+        if modifiers.is_empty() {
+            write!(f, "{{}}, \"{}\"", key)
+        } else {
+            write!(f, "{{ {} }}, \"{}\"", modifiers.join(", "), key)
+        }
+    }
+}
+
+#[test]
+fn awesome_keygrabbers() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| $TERMINAL -e sh -c "nmtui"
+|super Space; super Space; q|
+|super d| dmenu_run
+"#;
+    let expected = PRELUDE.to_string()
+        + r#"
+modes["super_space"] = awful.keygrabber {
+    stop_key = "Escape",
+    keybindings = {
+        { { "Mod4" }, "space", enter("super_space", "super_space__super_space") },
+        { { "Mod4" }, "w", spawn("super_space", "$TERMINAL -e sh -c \"nmtui\"") },
+    },
+}
+
+modes["super_space__super_space"] = awful.keygrabber {
+    stop_key = "Escape",
+    keybindings = {
+        { {}, "q", spawn("super_space__super_space", "") },
+    },
+}
+
+return gears.table.join(
+    awful.key({ "Mod4" }, "space", enter(nil, "super_space")),
+    awful.key({ "Mod4" }, "d", spawn(nil, "dmenu_run"))
+)
+"#;
+    assert_eq!(render_str(Format::Awesome, source), expected);
+    // A digit after an escape is not read as part of it
+    assert_eq!(
+        quote_string("\u{1}2", '"', ControlEscape::Decimal),
+        "\"\\0012\""
+    );
+}
@@ -0,0 +1,266 @@
+// Cheatsheets in Markdown or as a standalone HTML page
+//
+// Comments between entries like '# Main' are the section headings (but not
+// a '#!' shebang), and an entry with a '#: text' comment right above it
+// shows that text instead of its body. Within a section, chains are listed
+// under their keyspace
+
+use super::join_action;
+use crate::constants::{Chord, Hotkey, Shortcut};
+use crate::parser::is_comment_line;
+use std::fmt;
+
+pub struct Markdown<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>], pub &'x [&'a str]);
+pub struct Html<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>], pub &'x [&'a str]);
+
+struct Section<'x, 'a, 'b> {
+    heading: &'a str, // Empty for entries before the first heading
+    keyspaces: Vec<(&'b [Chord], Vec<Row<'x, 'a, 'b>>)>,
+}
+
+struct Row<'x, 'a, 'b> {
+    shortcut: &'x Shortcut<'a, 'b>,
+    description: Option<&'a str>,
+}
+
+impl Row<'_, '_, '_> {
+    // The chords after the keyspace title
+    fn chords(&self, title: &[Chord]) -> String {
+        Hotkey(&self.shortcut.hotkey.0[title.len()..]).to_string()
+    }
+
+    // Without the comments between it and the next entry, which are the
+    // headings and descriptions rather than part of what it runs
+    fn body(&self) -> String {
+        let body = join_action(self.shortcut.action);
+        let lines = body.lines().collect::<Vec<_>>();
+        let end = lines
+            .iter()
+            .rposition(|line| !is_comment_line(line) && !line.trim().is_empty())
+            .map_or(0, |i| i + 1);
+        lines[..end].join("\n")
+    }
+}
+
+fn sections<'x, 'a, 'b>(
+    list: &'x [Shortcut<'a, 'b>],
+    comments: &[&'a str],
+) -> Vec<Section<'x, 'a, 'b>> {
+    let headings = comments
+        .iter()
+        .filter(|comment| !comment.starts_with("#:") && !comment.starts_with("#!"))
+        .map(|comment| (*comment, comment.trim_start_matches('#').trim()))
+        .filter(|(_, heading)| !heading.is_empty())
+        .collect::<Vec<_>>();
+
+    let mut sections = vec![Section {
+        heading: "",
+        keyspaces: Vec::new(),
+    }];
+    sections.extend(headings.iter().map(|(_, heading)| Section {
+        heading,
+        keyspaces: Vec::new(),
+    }));
+
+    for shortcut in list {
+        // Plus one for the untitled section
+        let index = headings
+            .iter()
+            .take_while(|(comment, _)| comment.as_ptr() < shortcut.head.as_ptr())
+            .count();
+        let description = comments
+            .iter()
+            .find(|comment| comment.starts_with("#:") && is_right_before(comment, shortcut.head))
+            .map(|comment| comment["#:".len()..].trim());
+        let title = &shortcut.hotkey.0[..shortcut.hotkey.0.len() - 1];
+
+        let keyspaces = &mut sections[index].keyspaces;
+        let row = Row {
+            shortcut,
+            description,
+        };
+        match keyspaces.iter_mut().find(|(other, _)| *other == title) {
+            Some((_, rows)) => rows.push(row),
+            None => keyspaces.push((title, vec![row])),
+        }
+    }
+
+    sections.retain(|section| !section.keyspaces.is_empty());
+    for section in &mut sections {
+        // The root keyspace first, the rest in the order of the hotkeys
+        section
+            .keyspaces
+            .sort_by_key(|(title, _)| !title.is_empty());
+    }
+    sections
+}
+
+// If 'comment' is the line right before the line of 'head'
+fn is_right_before(comment: &str, head: &str) -> bool {
+    let comment_close = comment.as_ptr() as usize + comment.len();
+    let head_start = head.as_ptr() as usize;
+    // Between is the '\n|' or '\r\n|'
+    head_start > comment_close && head_start - comment_close <= "\r\n|".len()
+}
+
+impl fmt::Display for Markdown<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# Shortcuts")?;
+        for section in sections(self.0, self.1) {
+            if !section.heading.is_empty() {
+                writeln!(f, "\n## {}", section.heading)?;
+            }
+            for (title, rows) in &section.keyspaces {
+                if !title.is_empty() {
+                    let level = (2 + title.len()).min(6);
+                    writeln!(f, "\n{} {}", "#".repeat(level), Hotkey(title))?;
+                }
+                writeln!(f, "\n| Hotkey | Action |")?;
+                writeln!(f, "| --- | --- |")?;
+                for row in rows {
+                    let action = match row.description {
+                        Some(description) => description.replace('|', "\\|"),
+                        None => code_span(&row.body()),
+                    };
+                    writeln!(f, "| {} | {} |", row.chords(title), action)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// The body on one line, as newlines would end the table row
+fn code_span(body: &str) -> String {
+    let body = body.trim().replace('\n', " ").replace('|', "\\|");
+    if body.is_empty() {
+        return body;
+    }
+    let mut longest = 0;
+    let mut run = 0;
+    for ch in body.chars() {
+        run = if ch == '`' { run + 1 } else { 0 };
+        longest = longest.max(run);
+    }
+    let fence = "`".repeat(longest + 1);
+    if body.starts_with('`') || body.ends_with('`') {
+        format!("{} {} {}", fence, body, fence)
+    } else {
+        format!("{}{}{}", fence, body, fence)
+    }
+}
+
+const HTML_HEAD: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Shortcuts</title>
+<style>
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; vertical-align: top; }
+td:first-child { white-space: nowrap; }
+code { white-space: pre-wrap; }
+@media print { h2 { break-after: avoid; } table { break-inside: avoid; } }
+</style>
+</head>
+<body>
+<h1>Shortcuts</h1>
+"#;
+
+impl fmt::Display for Html<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(HTML_HEAD)?;
+        for section in sections(self.0, self.1) {
+            if !section.heading.is_empty() {
+                writeln!(f, "<h2>{}</h2>", html_escape(section.heading))?;
+            }
+            for (title, rows) in &section.keyspaces {
+                writeln!(f, "<div style=\"margin-left: {}em\">", title.len() * 2)?;
+                if !title.is_empty() {
+                    let level = (2 + title.len()).min(6);
+                    writeln!(
+                        f,
+                        "<h{0}>{1}</h{0}>",
+                        level,
+                        html_escape(&Hotkey(title).to_string())
+                    )?;
+                }
+                writeln!(f, "<table>")?;
+                writeln!(f, "<tr><th>Hotkey</th><th>Action</th></tr>")?;
+                for row in rows {
+                    let action = match row.description {
+                        Some(description) => html_escape(description),
+                        None => format!("<code>{}</code>", html_escape(row.body().trim())),
+                    };
+                    writeln!(
+                        f,
+                        "<tr><td>{}</td><td>{}</td></tr>",
+                        html_escape(&row.chords(title)),
+                        action
+                    )?;
+                }
+                writeln!(f, "</table>")?;
+                writeln!(f, "</div>")?;
+            }
+        }
+        writeln!(f, "</body>")?;
+        writeln!(f, "</html>")
+    }
+}
+
+fn html_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn cheatsheet_sections() {
+    use super::{render_str, Format};
+    let source = r#"#!/this/is/not/a/heading/either
+|super shift q|
+
+# Main
+|super Space; super w| nmtui | less
+#: Open a terminal
+|super Return| $TERMINAL
+# Tools
+|super d| dmenu_run
+"#;
+    assert_eq!(
+        render_str(Format::Markdown, source),
+        r#"# Shortcuts
+
+| Hotkey | Action |
+| --- | --- |
+| Shift + Super + q |  |
+
+## Main
+
+| Hotkey | Action |
+| --- | --- |
+| Super + Return | Open a terminal |
+
+### Super + Space
+
+| Hotkey | Action |
+| --- | --- |
+| Super + w | `nmtui \| less` |
+
+## Tools
+
+| Hotkey | Action |
+| --- | --- |
+| Super + d | `dmenu_run` |
+"#
+    );
+}
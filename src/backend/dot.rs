@@ -0,0 +1,112 @@
+// Graphviz DOT of the keyspace tree, for auditing how chains are laid out
+//
+// Keyspaces are the inner nodes and actions the leaves, with each edge
+// labelled by the chord that is pressed
+
+use super::{identifier, join_action, quote_string, ControlEscape};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Dot<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+// In chars, not counting the ellipsis
+const ACTION_LABEL_MAX: usize = 40;
+
+impl fmt::Display for Dot<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "digraph shortcuts {{")?;
+        writeln!(f, "  rankdir=LR;")?;
+        writeln!(f, "  node [fontname=\"monospace\"];")?;
+        writeln!(f, "  edge [fontname=\"monospace\"];")?;
+
+        let mut leaf_count = 0;
+        for keyspace in self.0 {
+            let node = node_id(keyspace);
+            let label = if keyspace.title.0.is_empty() {
+                "(root)".into()
+            } else {
+                keyspace.title.to_string()
+            };
+            writeln!(
+                f,
+                "\n  {} [label={}, shape=box];",
+                node,
+                quote_string(&label, '"', ControlEscape::Verbatim)
+            )?;
+
+            for (chord, action) in &keyspace.list {
+                let edge_label = quote_string(&chord.to_string(), '"', ControlEscape::Verbatim);
+                match action {
+                    KeyspaceAction::SetState(chord_list) => {
+                        let next =
+                            quote_string(&identifier(chord_list), '"', ControlEscape::Verbatim);
+                        writeln!(f, "  {} -> {} [label={}];", node, next, edge_label)?;
+                    }
+                    KeyspaceAction::Action(action) => {
+                        let leaf = format!("\"leaf{}\"", leaf_count);
+                        leaf_count += 1;
+                        writeln!(
+                            f,
+                            "  {} [label={}, shape=plaintext];",
+                            leaf,
+                            quote_string(
+                                &truncate(&join_action(action)),
+                                '"',
+                                ControlEscape::Verbatim
+                            )
+                        )?;
+                        writeln!(f, "  {} -> {} [label={}];", node, leaf, edge_label)?;
+                    }
+                }
+            }
+        }
+        writeln!(f, "}}")
+    }
+}
+
+fn node_id(keyspace: &Keyspace) -> String {
+    if keyspace.title.0.is_empty() {
+        "\"root\"".into()
+    } else {
+        quote_string(&identifier(keyspace.title.0), '"', ControlEscape::Verbatim)
+    }
+}
+
+// Only the first line, up to 'ACTION_LABEL_MAX' chars
+fn truncate(body: &str) -> String {
+    let body = body.trim();
+    let line = body.lines().next().unwrap_or("");
+    let mut label = line.chars().take(ACTION_LABEL_MAX).collect::<String>();
+    if label.len() < body.len() {
+        label.push('…');
+    }
+    label
+}
+
+#[test]
+fn dot_keyspace_tree() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| $TERMINAL -e sh -c "echo nmcli; sudo nmtui"; statusbar-startrefresh.sh
+|super d| dmenu_run
+  -p run
+"#;
+    assert_eq!(
+        render_str(Format::Dot, source),
+        r#"digraph shortcuts {
+  rankdir=LR;
+  node [fontname="monospace"];
+  edge [fontname="monospace"];
+
+  "root" [label="(root)", shape=box];
+  "root" -> "super_space" [label="Super + Space"];
+  "leaf0" [label="dmenu_run…", shape=plaintext];
+  "root" -> "leaf0" [label="Super + d"];
+
+  "super_space" [label="Super + Space", shape=box];
+  "leaf1" [label="$TERMINAL -e sh -c \"echo nmcli; sudo nmt…", shape=plaintext];
+  "super_space" -> "leaf1" [label="Super + w"];
+}
+"#
+    );
+}
@@ -0,0 +1,153 @@
+// dwm 'config.h', as the 'keys[]' array
+//
+// The keychord patch replaces 'keys[]' with 'keychords[]' (and changes what
+// 'Key' is), so if any hotkey is a chain then every hotkey uses that form
+
+use super::{join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use crate::error::StepError;
+use std::fmt;
+
+// Size of 'Keychord.keys' in the keychord patch
+const KEYCHORD_MAX: usize = 5;
+
+pub struct Dwm<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+pub fn check<'a>(list: &[Shortcut<'a, '_>]) -> Result<(), Vec<StepError<'a>>> {
+    let errors = list
+        .iter()
+        .filter(|shortcut| shortcut.hotkey.0.len() > KEYCHORD_MAX)
+        .map(|shortcut| {
+            StepError::new(
+                format!(
+                    "{} has more than {} chords, the most the dwm keychord patch supports",
+                    shortcut.hotkey, KEYCHORD_MAX
+                ),
+                shortcut.head,
+            )
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl fmt::Display for Dwm<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.iter().all(|shortcut| shortcut.hotkey.0.len() == 1) {
+            writeln!(f, "static Key keys[] = {{")?;
+            writeln!(
+                f,
+                "\t/* modifier                     key        function        argument */"
+            )?;
+            for shortcut in self.0 {
+                let chord = &shortcut.hotkey.0[0];
+                let (function, argument) = function_argument(shortcut);
+                writeln!(
+                    f,
+                    "\t{{ {:<30}{:<11}{:<16}{} }},",
+                    format!("{},", Mask(chord)),
+                    format!("{},", keysym(&chord.key)),
+                    format!("{},", function),
+                    argument
+                )?;
+            }
+        } else {
+            writeln!(f, "static Keychord *keychords[] = {{")?;
+            writeln!(f, "\t/* Keys        function        argument */")?;
+            for shortcut in self.0 {
+                let chords = shortcut
+                    .hotkey
+                    .0
+                    .iter()
+                    .map(|chord| format!("{{{}, {}}}", Mask(chord), keysym(&chord.key)))
+                    .collect::<Vec<_>>();
+                let (function, argument) = function_argument(shortcut);
+                writeln!(
+                    f,
+                    "\t&((Keychord){{{}, {{{}}}, {}, {} }}),",
+                    chords.len(),
+                    chords.join(", "),
+                    function,
+                    argument
+                )?;
+            }
+        }
+        writeln!(f, "}};")
+    }
+}
+
+fn function_argument(shortcut: &Shortcut) -> (&'static str, String) {
+    let body = join_action(shortcut.action);
+    if body.is_empty() {
+        ("NULL", "{0}".into())
+    } else {
+        // No escape writes a '?', so escaping them after avoids trigraphs like '??/'
+        let string = quote_string(&body, '"', ControlEscape::Octal).replace('?', "\\?");
+        ("spawn", format!("SHCMD({})", string))
+    }
+}
+
+struct Mask<'c>(&'c Chord);
+
+impl fmt::Display for Mask<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let masks = self
+            .0
+            .modifier_list()
+            .map(|modifier| match modifier {
+                Mod::Shift => "ShiftMask",
+                Mod::Super => "Mod4Mask",
+                Mod::Ctrl => "ControlMask",
+                Mod::Alt => "Mod1Mask",
+            })
+            .collect::<Vec<_>>();
+        if masks.is_empty() {
+            f.write_str("0")
+        } else {
+            f.write_str(&masks.join("|"))
+        }
+    }
+}
+
+fn keysym(key: &Key) -> String {
+    match key {
+        Key::Space => "XK_space".into(),
+        Key::Comma => "XK_comma".into(),
+        key => format!("XK_{}", key.as_str()),
+    }
+}
+
+#[test]
+fn dwm_keys() {
+    use super::{render_str, Format};
+    let source = r#"
+|super d| dmenu_run -p "run?"
+|super shift q|
+"#;
+    assert_eq!(
+        render_str(Format::Dwm, source),
+        r#"static Key keys[] = {
+	/* modifier                     key        function        argument */
+	{ Mod4Mask,                     XK_d,      spawn,          SHCMD("dmenu_run -p \"run\?\"") },
+	{ ShiftMask|Mod4Mask,           XK_q,      NULL,           {0} },
+};
+"#
+    );
+
+    let source = r#"
+|super d| printf '%s\n' a
+|super Space; super w| nmtui
+"#;
+    assert_eq!(
+        render_str(Format::Dwm, source),
+        r#"static Keychord *keychords[] = {
+	/* Keys        function        argument */
+	&((Keychord){2, {{Mod4Mask, XK_space}, {Mod4Mask, XK_w}}, spawn, SHCMD("nmtui") }),
+	&((Keychord){1, {{Mod4Mask, XK_d}}, spawn, SHCMD("printf '%s\\n' a") }),
+};
+"#
+    );
+}
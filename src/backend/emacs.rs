@@ -0,0 +1,142 @@
+// Emacs, as an '.el' file of global bindings
+//
+// Every keyspace other than the root becomes a prefix command that is bound
+// in its parent. A body of just "#'command" binds that command instead of
+// running the body with a shell
+
+use super::{identifier, join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Emacs<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for Emacs<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            ";;; shortcuts.el --- Global key bindings -*- lexical-binding: t -*-"
+        )?;
+        for keyspace in self.0.iter().skip(1) {
+            let name = prefix_name(keyspace.title.0);
+            writeln!(f, "\n(define-prefix-command '{})", name)?;
+            for (chord, action) in &keyspace.list {
+                writeln!(
+                    f,
+                    "(define-key {} (kbd \"{}\") {})",
+                    name,
+                    EmacsChord(chord),
+                    command(action)
+                )?;
+            }
+        }
+
+        if let Some(root) = self.0.first() {
+            writeln!(f)?;
+            for (chord, action) in &root.list {
+                writeln!(
+                    f,
+                    "(global-set-key (kbd \"{}\") {})",
+                    EmacsChord(chord),
+                    command(action)
+                )?;
+            }
+        }
+        writeln!(f, "\n(provide 'shortcuts)")?;
+        writeln!(f, ";;; shortcuts.el ends here")
+    }
+}
+
+fn prefix_name(title: &[Chord]) -> String {
+    format!("shortcuts-{}", identifier(title))
+}
+
+fn command(action: &KeyspaceAction) -> String {
+    match action {
+        KeyspaceAction::SetState(chord_list) => format!("'{}", prefix_name(chord_list)),
+        KeyspaceAction::Action(action) => {
+            let body = join_action(action);
+            let body = body.trim();
+            let is_symbol = |s: &str| {
+                !s.is_empty() && !s.contains(|c: char| c.is_whitespace() || "()\"';`,#".contains(c))
+            };
+            match body.strip_prefix("#'") {
+                Some(symbol) if is_symbol(symbol) => body.into(),
+                _ if body.is_empty() => "#'ignore".into(),
+                _ => format!(
+                    "(lambda () (interactive) (start-process-shell-command \"shortcuts\" nil {}))",
+                    quote_string(body, '"', ControlEscape::Octal)
+                ),
+            }
+        }
+    }
+}
+
+// 'kbd' notation, e.g. 'C-M-s-SPC'
+struct EmacsChord<'c>(&'c Chord);
+
+impl fmt::Display for EmacsChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Emacs orders them as C, M, S then s
+        let mut names = [None; 4];
+        for modifier in self.0.modifier_list() {
+            let (i, name) = match modifier {
+                Mod::Ctrl => (0, "C-"),
+                Mod::Alt => (1, "M-"),
+                Mod::Shift => (2, "S-"),
+                Mod::Super => (3, "s-"),
+            };
+            names[i] = Some(name);
+        }
+        let key = match self.0.key {
+            Key::Space => "SPC",
+            Key::Return => "RET",
+            Key::Comma => ",",
+            ref key => key.as_str(),
+        };
+        // Emacs sees shift with a letter as the uppercase letter, but not with
+        // control, where 'C-A' is read as 'C-a'
+        let is_shifted_letter = names[2].is_some()
+            && names[0].is_none()
+            && key.len() == 1
+            && key.chars().all(char::is_alphabetic);
+        if is_shifted_letter {
+            names[2] = None;
+        }
+        for name in names.iter().flatten() {
+            f.write_str(name)?;
+        }
+        if is_shifted_letter {
+            f.write_str(&key.to_uppercase())
+        } else {
+            f.write_str(key)
+        }
+    }
+}
+
+#[test]
+fn emacs_prefix_keymaps() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| $TERMINAL -e sh -c "nmtui"
+|super Space; ctrl alt shift g| #'magit-status
+|super shift Return|
+|alt shift b| #'backward-word
+"#;
+    assert_eq!(
+        render_str(Format::Emacs, source),
+        r#";;; shortcuts.el --- Global key bindings -*- lexical-binding: t -*-
+
+(define-prefix-command 'shortcuts-super_space)
+(define-key shortcuts-super_space (kbd "C-M-S-g") #'magit-status)
+(define-key shortcuts-super_space (kbd "s-w") (lambda () (interactive) (start-process-shell-command "shortcuts" nil "$TERMINAL -e sh -c \"nmtui\"")))
+
+(global-set-key (kbd "s-SPC") 'shortcuts-super_space)
+(global-set-key (kbd "M-B") #'backward-word)
+(global-set-key (kbd "S-s-RET") #'ignore)
+
+(provide 'shortcuts)
+;;; shortcuts.el ends here
+"#
+    );
+}
@@ -0,0 +1,175 @@
+// Fluxbox '~/.fluxbox/keys', which supports chains natively
+//
+// Keyspaces are chains by default. Those named with '--keymode' become a
+// 'KeyMode' instead, i.e. they stay active until an action or Escape, and
+// their lines are prefixed with the mode name
+
+use super::{find_keyspace, identifier, join_action, shell_one_line};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Fluxbox<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>], pub &'x [String]);
+
+// The identifiers of the keyspaces named with '--keymode', which is either
+// the identifier (e.g. 'super_space') or the title ('Super + Space')
+pub fn keymode_identifiers(list: &[Keyspace], keymodes: &[String]) -> Result<Vec<String>, String> {
+    let names = list
+        .iter()
+        .skip(1)
+        .map(|keyspace| (keyspace.title.to_string(), identifier(keyspace.title.0)))
+        .collect::<Vec<_>>();
+    keymodes
+        .iter()
+        .map(|name| {
+            names
+                .iter()
+                .find(|(title, id)| id == name || title.eq_ignore_ascii_case(name))
+                .map(|(_, id)| id.clone())
+                .ok_or_else(|| {
+                    let expected = names
+                        .iter()
+                        .map(|(title, id)| format!("{:?} ({})", title, id))
+                        .collect::<Vec<_>>();
+                    format!(
+                        "No keyspace named {:?} for '--keymode'. Expected one of {}",
+                        name,
+                        expected.join(", ")
+                    )
+                })
+        })
+        .collect()
+}
+
+impl fmt::Display for Fluxbox<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(root) = self.0.first() {
+            self.write_keyspace(f, root, None, &mut Vec::new())?;
+        }
+        Ok(())
+    }
+}
+
+impl Fluxbox<'_, '_, '_> {
+    // 'chain' is the chords pressed since the start of 'mode'
+    fn write_keyspace(
+        &self,
+        f: &mut fmt::Formatter,
+        keyspace: &Keyspace,
+        mode: Option<&str>,
+        chain: &mut Vec<String>,
+    ) -> fmt::Result {
+        for (chord, action) in &keyspace.list {
+            chain.push(FluxboxChord(chord).to_string());
+            match action {
+                KeyspaceAction::SetState(chord_list) => {
+                    let name = identifier(chord_list);
+                    let next = find_keyspace(self.0, chord_list);
+                    if self.1.contains(&name) {
+                        self.write_line(f, mode, chain)?;
+                        writeln!(f, ":KeyMode {} Escape", name)?;
+                        self.write_keyspace(f, next, Some(&name), &mut Vec::new())?;
+                    } else {
+                        self.write_keyspace(f, next, mode, chain)?;
+                    }
+                }
+                KeyspaceAction::Action(action) => {
+                    let body = join_action(action);
+                    self.write_line(f, mode, chain)?;
+                    match (mode, body.is_empty()) {
+                        (None, true) => writeln!(f, ":Exec true")?,
+                        (None, false) => writeln!(f, ":Exec {}", shell_one_line(&body))?,
+                        (Some(_), true) => writeln!(f, ":KeyMode default")?,
+                        (Some(_), false) => writeln!(
+                            f,
+                            ":MacroCmd {{KeyMode default}} {{Exec {}}}",
+                            shell_one_line(&body)
+                        )?,
+                    }
+                }
+            }
+            chain.pop();
+        }
+        Ok(())
+    }
+
+    // Everything before the command, which starts with ':'
+    fn write_line(
+        &self,
+        f: &mut fmt::Formatter,
+        mode: Option<&str>,
+        chain: &[String],
+    ) -> fmt::Result {
+        if let Some(name) = mode {
+            write!(f, "{}: ", name)?;
+        }
+        write!(f, "{} ", chain.join(" "))
+    }
+}
+
+struct FluxboxChord<'c>(&'c Chord);
+
+impl fmt::Display for FluxboxChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "Shift",
+                Mod::Super => "Mod4",
+                Mod::Ctrl => "Control",
+                Mod::Alt => "Mod1",
+            };
+            write!(f, "{} ", name)?;
+        }
+        match self.0.key {
+            Key::Space => f.write_str("space"),
+            Key::Comma => f.write_str("comma"),
+            ref key => f.write_str(key.as_str()),
+        }
+    }
+}
+
+#[test]
+fn fluxbox_chains_and_keymodes() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| nmtui
+|super Space; ctrl Space; {{b,c}}| mpc {{prev,next}}
+|alt Return|
+"#;
+    assert_eq!(
+        render_str(Format::Fluxbox(Vec::new()), source),
+        r#"Mod4 space Control space b :Exec mpc prev
+Mod4 space Control space c :Exec mpc next
+Mod4 space Mod4 w :Exec nmtui
+Mod1 Return :Exec true
+"#
+    );
+    assert_eq!(
+        render_str(
+            Format::Fluxbox(vec!["super_space__ctrl_space".into()]),
+            source
+        ),
+        r#"Mod4 space Control space :KeyMode super_space__ctrl_space Escape
+super_space__ctrl_space: b :MacroCmd {KeyMode default} {Exec mpc prev}
+super_space__ctrl_space: c :MacroCmd {KeyMode default} {Exec mpc next}
+Mod4 space Mod4 w :Exec nmtui
+Mod1 Return :Exec true
+"#
+    );
+
+    use crate::keyspace::KeyspaceList;
+    use crate::parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    let list = generator.allocate_keyspace_list().unwrap();
+    let keymodes = ["super_space".into(), "super + space ; ctrl + space".into()];
+    assert_eq!(
+        keymode_identifiers(&list, &keymodes),
+        Ok(vec!["super_space".into(), "super_space__ctrl_space".into()])
+    );
+    assert_eq!(
+        keymode_identifiers(&list, &["nope".into()]),
+        Err(r#"No keyspace named "nope" for '--keymode'. Expected one of "Super + Space" (super_space), "Super + Space ; Ctrl + Space" (super_space__ctrl_space)"#.into())
+    );
+}
@@ -0,0 +1,95 @@
+// GNOME custom shortcuts, as a keyfile for 'dconf load /'
+//
+// The command is not run through a shell
+
+use super::{join_action, quote_string, sh_quote, ControlEscape};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use std::fmt;
+
+pub struct Gnome<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+const MEDIA_KEYS: &str = "org/gnome/settings-daemon/plugins/media-keys";
+
+impl fmt::Display for Gnome<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let paths = (0..self.0.len())
+            .map(|i| format!("'/{}/custom-keybindings/custom{}/'", MEDIA_KEYS, i))
+            .collect::<Vec<_>>();
+        writeln!(f, "[{}]", MEDIA_KEYS)?;
+        writeln!(f, "custom-keybindings=[{}]", paths.join(", "))?;
+
+        for (i, shortcut) in self.0.iter().enumerate() {
+            let body = join_action(shortcut.action);
+            let command = if body.is_empty() {
+                "true".into()
+            } else {
+                format!("sh -c {}", sh_quote(&body))
+            };
+            writeln!(f, "\n[{}/custom-keybindings/custom{}]", MEDIA_KEYS, i)?;
+            let binding = GnomeChord(&shortcut.hotkey.0[0]).to_string();
+            writeln!(
+                f,
+                "binding={}",
+                quote_string(&binding, '\'', ControlEscape::Unicode)
+            )?;
+            writeln!(
+                f,
+                "command={}",
+                quote_string(&command, '\'', ControlEscape::Unicode)
+            )?;
+            writeln!(
+                f,
+                "name={}",
+                quote_string(&shortcut.hotkey.to_string(), '\'', ControlEscape::Unicode)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+// GTK accelerator syntax, e.g. '<Shift><Super>Return'
+struct GnomeChord<'c>(&'c Chord);
+
+impl fmt::Display for GnomeChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "<Shift>",
+                Mod::Super => "<Super>",
+                Mod::Ctrl => "<Primary>",
+                Mod::Alt => "<Alt>",
+            };
+            f.write_str(name)?;
+        }
+        match self.0.key {
+            Key::Space => f.write_str("space"),
+            Key::Comma => f.write_str("comma"),
+            ref key => f.write_str(key.as_str()),
+        }
+    }
+}
+
+#[test]
+fn gnome_custom_keybindings() {
+    use super::{render_str, Format};
+    let source = r#"
+|super d| dmenu_run -p 'run:'
+|ctrl alt Return|
+"#;
+    assert_eq!(
+        render_str(Format::Gnome, source),
+        r#"[org/gnome/settings-daemon/plugins/media-keys]
+custom-keybindings=['/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0/', '/org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom1/']
+
+[org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom0]
+binding='<Super>d'
+command='sh -c \'dmenu_run -p \'\\\'\'run:\'\\\'\'\''
+name='Super + d'
+
+[org/gnome/settings-daemon/plugins/media-keys/custom-keybindings/custom1]
+binding='<Primary><Alt>Return'
+command='true'
+name='Ctrl + Alt + Return'
+"#
+    );
+}
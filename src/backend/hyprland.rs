@@ -0,0 +1,100 @@
+// Hyprland, where each keyspace other than the root becomes a submap
+//
+// As a key can have several binds, leaving a submap after an action is just
+// a second bind on the same key
+
+use super::{identifier, join_action, shell_one_line};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Hyprland<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for Hyprland<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for keyspace in self.0 {
+            let is_root = keyspace.title.0.is_empty();
+            if !is_root {
+                writeln!(f, "\nsubmap = {}", identifier(keyspace.title.0))?;
+            }
+
+            for (chord, action) in &keyspace.list {
+                let bind = HyprlandChord(chord);
+                match action {
+                    KeyspaceAction::SetState(chord_list) => {
+                        writeln!(f, "bind = {}, submap, {}", bind, identifier(chord_list))?;
+                    }
+                    KeyspaceAction::Action(action) => {
+                        let body = join_action(action);
+                        let command = if body.is_empty() {
+                            "true".into()
+                        } else {
+                            shell_one_line(&body).replace('#', "##")
+                        };
+                        writeln!(f, "bind = {}, exec, {}", bind, command)?;
+                        if !is_root {
+                            writeln!(f, "bind = {}, submap, reset", bind)?;
+                        }
+                    }
+                }
+            }
+
+            if !is_root {
+                writeln!(f, "bind = , escape, submap, reset")?;
+                // Any other key also leaves the submap
+                writeln!(f, "bind = , catchall, submap, reset")?;
+                writeln!(f, "submap = reset")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The 'MODS, key' part of a bind
+struct HyprlandChord<'c>(&'c Chord);
+
+impl fmt::Display for HyprlandChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mask = self
+            .0
+            .modifier_list()
+            .map(|modifier| match modifier {
+                Mod::Shift => "SHIFT",
+                Mod::Super => "SUPER",
+                Mod::Ctrl => "CTRL",
+                Mod::Alt => "ALT",
+            })
+            .collect::<Vec<_>>();
+        let key = match self.0.key {
+            Key::Space => "space".into(),
+            Key::Comma => "comma".into(),
+            Key::Return => "Return".into(),
+            ref key => key.as_str().to_uppercase(),
+        };
+        write!(f, "{}, {}", mask.join(" "), key)
+    }
+}
+
+#[test]
+fn hyprland_submaps() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| notify-send '#1'
+|super shift q| hyprctl dispatch exit
+|ctrl alt Return|
+"#;
+    assert_eq!(
+        render_str(Format::Hyprland, source),
+        r#"bind = SUPER, space, submap, super_space
+bind = SHIFT SUPER, Q, exec, hyprctl dispatch exit
+bind = CTRL ALT, Return, exec, true
+
+submap = super_space
+bind = SUPER, W, exec, notify-send '##1'
+bind = SUPER, W, submap, reset
+bind = , escape, submap, reset
+bind = , catchall, submap, reset
+submap = reset
+"#
+    );
+}
@@ -0,0 +1,106 @@
+// i3 and sway, which share the same config syntax
+//
+// The root keyspace becomes top-level 'bindsym's and every other keyspace
+// becomes a 'mode' that returns to "default" after running an action
+
+use super::{join_action, shell_one_line};
+use crate::constants::{Chord, Hotkey, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct I3<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for I3<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for keyspace in self.0 {
+            let is_root = keyspace.title.0.is_empty();
+            let padding = if is_root { "" } else { "  " };
+            if !is_root {
+                writeln!(f, "\nmode {} {{", quote(&keyspace.title.to_string()))?;
+            }
+
+            for (chord, action) in &keyspace.list {
+                write!(f, "{}bindsym {} ", padding, I3Chord(chord))?;
+                match action {
+                    KeyspaceAction::SetState(chord_list) => {
+                        writeln!(f, "mode {}", quote(&Hotkey(chord_list).to_string()))?;
+                    }
+                    KeyspaceAction::Action(action) => {
+                        let body = join_action(action);
+                        if body.is_empty() {
+                            write!(f, "nop")?;
+                        } else {
+                            let command = shell_one_line(&body);
+                            write!(f, "exec --no-startup-id {}", quote(&command))?;
+                        }
+                        if !is_root {
+                            write!(f, "; mode \"default\"")?;
+                        }
+                        writeln!(f)?;
+                    }
+                }
+            }
+
+            if !is_root {
+                writeln!(f, "  bindsym Escape mode \"default\"")?;
+                writeln!(f, "}}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+struct I3Chord<'c>(&'c Chord);
+
+impl fmt::Display for I3Chord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "Shift",
+                Mod::Super => "Mod4",
+                Mod::Ctrl => "Control",
+                Mod::Alt => "Mod1",
+            };
+            write!(f, "{}+", name)?;
+        }
+        let key = match self.0.key {
+            Key::Space => "space",
+            Key::Comma => "comma",
+            ref key => key.as_str(),
+        };
+        f.write_str(key)
+    }
+}
+
+// i3 only unescapes '\"' and '\\' inside of double quotes
+pub fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[test]
+fn i3_modes() {
+    use super::{render_str, Format};
+    let source = r#"
+|super {{a,b}}| echo {{1,2}}
+|super Space; super e| emacs "$HOME"
+|super Space; super Space; ctrl Comma|
+"#;
+    assert_eq!(
+        render_str(Format::I3, source),
+        r#"bindsym Mod4+space mode "Super + Space"
+bindsym Mod4+a exec --no-startup-id "echo 1"
+bindsym Mod4+b exec --no-startup-id "echo 2"
+
+mode "Super + Space" {
+  bindsym Mod4+space mode "Super + Space ; Super + Space"
+  bindsym Mod4+e exec --no-startup-id "emacs \"$HOME\""; mode "default"
+  bindsym Escape mode "default"
+}
+
+mode "Super + Space ; Super + Space" {
+  bindsym Control+comma nop; mode "default"
+  bindsym Escape mode "default"
+}
+"#
+    );
+}
@@ -0,0 +1,162 @@
+// JSON, for tooling that wants the expanded shortcuts and keyspaces
+//
+// The schema is versioned by 'JSON_VERSION', which must be bumped on any
+// change that is not just adding a new field:
+//
+//   {
+//     "version": 1,
+//     "shortcuts": [{
+//       "hotkey": [<chord>, ...],
+//       "action": "<body>",
+//       "row": <row of the entry in the config, 1-indexed>,
+//       "choices": [{"set": "{{a,b}}", "choice": "a"}, ...]
+//     }, ...],
+//     "keyspaces": [{
+//       "title": [<chord>, ...],
+//       "entries": [
+//         {"chord": <chord>, "type": "set-state", "keyspace": [<chord>, ...]},
+//         {"chord": <chord>, "type": "action", "action": "<body>"}
+//       ]
+//     }, ...]
+//   }
+//
+// where a <chord> is '{"modifiers": ["super", ...], "key": "Space"}' with
+// the same spelling as the config. The root keyspace has an empty title
+
+use super::{join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Shortcut};
+use crate::error::locate;
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub const JSON_VERSION: usize = 1;
+
+// The source is only for the rows
+pub struct Json<'x, 'a, 'b>(
+    pub &'x str,
+    pub &'x [Shortcut<'a, 'b>],
+    pub &'x [Keyspace<'a, 'b>],
+);
+
+impl fmt::Display for Json<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{{")?;
+        writeln!(f, "  \"version\": {},", JSON_VERSION)?;
+
+        writeln!(f, "  \"shortcuts\": [")?;
+        for (i, shortcut) in self.1.iter().enumerate() {
+            let choices = shortcut
+                .set_choices()
+                .iter()
+                .map(|(set, choice)| {
+                    format!(
+                        "{{\"set\": {}, \"choice\": {}}}",
+                        json_string(set),
+                        json_string(choice)
+                    )
+                })
+                .collect::<Vec<_>>();
+            let (row, _, _) = locate(self.0, shortcut.head);
+            write!(
+                f,
+                "    {{\"hotkey\": {}, \"action\": {}, \"row\": {}, \"choices\": [{}]}}",
+                ChordList(shortcut.hotkey.0),
+                json_string(&join_action(shortcut.action)),
+                row,
+                choices.join(", ")
+            )?;
+            writeln!(f, "{}", if i + 1 < self.1.len() { "," } else { "" })?;
+        }
+        writeln!(f, "  ],")?;
+
+        writeln!(f, "  \"keyspaces\": [")?;
+        for (i, keyspace) in self.2.iter().enumerate() {
+            let entries = keyspace
+                .list
+                .iter()
+                .map(|(chord, action)| match action {
+                    KeyspaceAction::SetState(chord_list) => format!(
+                        "{{\"chord\": {}, \"type\": \"set-state\", \"keyspace\": {}}}",
+                        JsonChord(chord),
+                        ChordList(chord_list)
+                    ),
+                    KeyspaceAction::Action(action) => format!(
+                        "{{\"chord\": {}, \"type\": \"action\", \"action\": {}}}",
+                        JsonChord(chord),
+                        json_string(&join_action(action))
+                    ),
+                })
+                .collect::<Vec<_>>();
+            write!(
+                f,
+                "    {{\"title\": {}, \"entries\": [{}]}}",
+                ChordList(keyspace.title.0),
+                entries.join(", ")
+            )?;
+            writeln!(f, "{}", if i + 1 < self.2.len() { "," } else { "" })?;
+        }
+        writeln!(f, "  ]")?;
+        writeln!(f, "}}")
+    }
+}
+
+struct ChordList<'c>(&'c [Chord]);
+
+impl fmt::Display for ChordList<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let chords = self
+            .0
+            .iter()
+            .map(|chord| JsonChord(chord).to_string())
+            .collect::<Vec<_>>();
+        write!(f, "[{}]", chords.join(", "))
+    }
+}
+
+struct JsonChord<'c>(&'c Chord);
+
+impl fmt::Display for JsonChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = self
+            .0
+            .modifier_list()
+            .map(|modifier| format!("\"{}\"", format!("{:?}", modifier).to_lowercase()))
+            .collect::<Vec<_>>();
+        write!(
+            f,
+            "{{\"modifiers\": [{}], \"key\": {}}}",
+            modifiers.join(", "),
+            json_string(self.0.key.as_str())
+        )
+    }
+}
+
+// Also for YAML, which accepts JSON strings
+pub fn json_string(s: &str) -> String {
+    quote_string(s, '"', ControlEscape::Unicode)
+}
+
+#[test]
+fn json_schema() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super {{a,b}}| echo "{{1,2}}"
+|alt Return|
+"#;
+    assert_eq!(
+        render_str(Format::Json, source),
+        r#"{
+  "version": 1,
+  "shortcuts": [
+    {"hotkey": [{"modifiers": ["super"], "key": "Space"}, {"modifiers": ["super"], "key": "a"}], "action": "echo \"1\"", "row": 2, "choices": [{"set": "{{a,b}}", "choice": "a"}]},
+    {"hotkey": [{"modifiers": ["super"], "key": "Space"}, {"modifiers": ["super"], "key": "b"}], "action": "echo \"2\"", "row": 2, "choices": [{"set": "{{a,b}}", "choice": "b"}]},
+    {"hotkey": [{"modifiers": ["alt"], "key": "Return"}], "action": "", "row": 3, "choices": []}
+  ],
+  "keyspaces": [
+    {"title": [], "entries": [{"chord": {"modifiers": ["super"], "key": "Space"}, "type": "set-state", "keyspace": [{"modifiers": ["super"], "key": "Space"}]}, {"chord": {"modifiers": ["alt"], "key": "Return"}, "type": "action", "action": ""}]},
+    {"title": [{"modifiers": ["super"], "key": "Space"}], "entries": [{"chord": {"modifiers": ["super"], "key": "a"}, "type": "action", "action": "echo \"1\""}, {"chord": {"modifiers": ["super"], "key": "b"}, "type": "action", "action": "echo \"2\""}]}
+  ]
+}
+"#
+    );
+}
@@ -0,0 +1,140 @@
+// KDE Plasma custom shortcuts, as the two files Plasma reads them from
+//
+// Plasma launches a command shortcut from an action of a '.desktop' file, so
+// every shortcut is a '[Desktop Action]' of one 'shortcuts.desktop' (for
+// '~/.local/share/applications'), and is bound in 'kglobalshortcutsrc' under
+// the 'services' group by the name of that action
+
+use super::{identifier, join_action};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use std::fmt;
+
+// The '[services]' group for 'kglobalshortcutsrc'
+pub struct Kde<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+// The 'shortcuts.desktop' file the actions are in
+pub struct KdeDesktop<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+const DESKTOP_FILE: &str = "shortcuts.desktop";
+
+impl fmt::Display for Kde<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "[services][{}]", DESKTOP_FILE)?;
+        for shortcut in self.0 {
+            let chord = &shortcut.hotkey.0[0];
+            writeln!(f, "{}={}", identifier(shortcut.hotkey.0), KdeChord(chord))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for KdeDesktop<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let actions = self
+            .0
+            .iter()
+            .map(|shortcut| format!("{};", identifier(shortcut.hotkey.0)))
+            .collect::<String>();
+        writeln!(f, "[Desktop Entry]")?;
+        writeln!(f, "Type=Application")?;
+        writeln!(f, "Name=Shortcuts")?;
+        writeln!(f, "Exec=true")?;
+        writeln!(f, "NoDisplay=true")?;
+        writeln!(f, "X-KDE-GlobalAccel-CommandShortcut=true")?;
+        writeln!(f, "Actions={}", actions)?;
+        for shortcut in self.0 {
+            let body = join_action(shortcut.action);
+            let exec = if body.is_empty() {
+                "true".into()
+            } else {
+                format!("sh -c \"{}\"", desktop_exec_escape(&body))
+            };
+            writeln!(f)?;
+            writeln!(f, "[Desktop Action {}]", identifier(shortcut.hotkey.0))?;
+            writeln!(f, "Name={}", shortcut.hotkey)?;
+            writeln!(f, "Exec={}", exec)?;
+        }
+        Ok(())
+    }
+}
+
+// Qt key sequence syntax, e.g. 'Meta+Shift+Return'
+struct KdeChord<'c>(&'c Chord);
+
+impl fmt::Display for KdeChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Qt orders them as Meta, Ctrl, Alt then Shift
+        let mut names = [None; 4];
+        for modifier in self.0.modifier_list() {
+            let (i, name) = match modifier {
+                Mod::Super => (0, "Meta"),
+                Mod::Ctrl => (1, "Ctrl"),
+                Mod::Alt => (2, "Alt"),
+                Mod::Shift => (3, "Shift"),
+            };
+            names[i] = Some(name);
+        }
+        for name in names.iter().flatten() {
+            write!(f, "{}+", name)?;
+        }
+        match self.0.key {
+            Key::Space | Key::Return => f.write_str(self.0.key.as_str()),
+            Key::Comma => f.write_str(","),
+            ref key => f.write_str(&key.as_str().to_uppercase()),
+        }
+    }
+}
+
+// For inside of a double-quoted argument of 'Exec', then as a string value
+fn desktop_exec_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '"' | '`' | '$' => {
+                escaped.push_str("\\\\");
+                escaped.push(ch);
+            }
+            '\\' => escaped.push_str("\\\\\\\\"),
+            '%' => escaped.push_str("%%"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn kde_desktop_entries() {
+    use super::{render_str, Format};
+    let source = r#"
+|super shift Return| printf '%s\n' "$TERMINAL"
+|ctrl alt Comma|
+"#;
+    assert_eq!(
+        render_str(Format::Kde, source),
+        r#"[services][shortcuts.desktop]
+shift_super_return=Meta+Shift+Return
+ctrl_alt_comma=Ctrl+Alt+,
+"#
+    );
+    assert_eq!(
+        render_str(Format::KdeDesktop, source),
+        r#"[Desktop Entry]
+Type=Application
+Name=Shortcuts
+Exec=true
+NoDisplay=true
+X-KDE-GlobalAccel-CommandShortcut=true
+Actions=shift_super_return;ctrl_alt_comma;
+
+[Desktop Action shift_super_return]
+Name=Shift + Super + Return
+Exec=sh -c "printf '%%s\\\\n' \\"\\$TERMINAL\\""
+
+[Desktop Action ctrl_alt_comma]
+Name=Ctrl + Alt + Comma
+Exec=true
+"#
+    );
+}
@@ -0,0 +1,173 @@
+// keyd, as the '[main]' and layer sections of its ini config
+//
+// Modifiers are layers in keyd, e.g. 'super d' is 'd' in '[meta]'. Every
+// other keyspace is a layer entered with 'oneshot()', which keyd leaves after
+// the next key press. That key is looked up in the keyspace layer whatever
+// modifiers are held, so chords that only differ by modifiers after the first
+// are an error. Note that keyd runs 'command()' as root
+
+use super::{identifier, join_action, shell_one_line};
+use crate::constants::{Chord, Hotkey, Key, Mod, Shortcut};
+use crate::error::StepError;
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+pub struct Keyd<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+pub fn check<'a>(list: &[Shortcut<'a, '_>]) -> Result<(), Vec<StepError<'a>>> {
+    let mut first_seen = BTreeMap::new();
+    let mut reported = BTreeSet::new();
+    let mut errors = Vec::new();
+    for shortcut in list {
+        for (i, chord) in shortcut.hotkey.0.iter().enumerate().skip(1) {
+            let title = &shortcut.hotkey.0[..i];
+            let (other_chord, other) = *first_seen
+                .entry((title, &chord.key))
+                .or_insert((chord, shortcut));
+            if other_chord != chord && reported.insert((title, other_chord, chord)) {
+                errors.push(
+                    StepError::new(
+                        format!(
+                            "After {}, keyd cannot tell {} apart from {} as only the key is used",
+                            Hotkey(title),
+                            chord,
+                            other_chord
+                        ),
+                        shortcut.head,
+                    )
+                    .with_note(format!("{} is defined here", other.hotkey), other.head),
+                );
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl fmt::Display for Keyd<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (root, rest) = match self.0.split_first() {
+            Some(split) => split,
+            None => return Ok(()),
+        };
+
+        // The root keyspace is split by which modifier layer it is in
+        let mut layers: Vec<(String, Vec<String>)> = Vec::new();
+        for (chord, action) in &root.list {
+            let name = layer_name(chord);
+            let line = binding(&chord.key, action);
+            match layers.iter_mut().find(|(other, _)| *other == name) {
+                Some((_, lines)) => lines.push(line),
+                None => layers.push((name, vec![line])),
+            }
+        }
+        layers.sort_by_key(|(name, _)| name != "main");
+
+        let mut is_first = true;
+        for (name, lines) in &layers {
+            if !is_first {
+                writeln!(f)?;
+            }
+            is_first = false;
+            writeln!(f, "[{}]", name)?;
+            for line in lines {
+                writeln!(f, "{}", line)?;
+            }
+        }
+
+        for keyspace in rest {
+            writeln!(f, "\n[{}]", identifier(keyspace.title.0))?;
+            for (chord, action) in &keyspace.list {
+                writeln!(f, "{}", binding(&chord.key, action))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn binding(key: &Key, action: &KeyspaceAction) -> String {
+    match action {
+        KeyspaceAction::SetState(chord_list) => {
+            format!("{} = oneshot({})", key_name(key), identifier(chord_list))
+        }
+        KeyspaceAction::Action(action) => {
+            let body = join_action(action);
+            if body.is_empty() {
+                format!("{} = noop", key_name(key))
+            } else {
+                format!("{} = command({})", key_name(key), shell_one_line(&body))
+            }
+        }
+    }
+}
+
+// 'main' or a (composite) modifier layer, e.g. 'control+alt'
+fn layer_name(chord: &Chord) -> String {
+    let mut names = [None; 4];
+    for modifier in chord.modifier_list() {
+        let (i, name) = match modifier {
+            Mod::Ctrl => (0, "control"),
+            Mod::Super => (1, "meta"),
+            Mod::Alt => (2, "alt"),
+            Mod::Shift => (3, "shift"),
+        };
+        names[i] = Some(name);
+    }
+    let names = names.iter().flatten().copied().collect::<Vec<_>>();
+    if names.is_empty() {
+        "main".into()
+    } else {
+        names.join("+")
+    }
+}
+
+fn key_name(key: &Key) -> String {
+    match key {
+        Key::Return => "enter".into(),
+        key => key.as_str().to_lowercase(),
+    }
+}
+
+#[test]
+fn keyd_layers() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| nmtui
+|super Space; ctrl Space; b|
+|super d| dmenu_run
+|f|
+|ctrl alt Return| foot
+"#;
+    assert_eq!(
+        render_str(Format::Keyd, source),
+        r#"[main]
+f = noop
+
+[meta]
+space = oneshot(super_space)
+d = command(dmenu_run)
+
+[control+alt]
+enter = command(foot)
+
+[super_space]
+space = oneshot(super_space__ctrl_space)
+w = command(nmtui)
+
+[super_space__ctrl_space]
+b = noop
+"#
+    );
+
+    use crate::parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+    let source = "|super Space; {{super a, a}}| echo {{1,2}}\n";
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    let errors = Format::Keyd.render(&generator, source).unwrap_err();
+    assert_eq!(errors.len(), 1);
+}
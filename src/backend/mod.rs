@@ -0,0 +1,346 @@
+// Native renderers for each of the supported targets
+//
+// Each target is a wrapper struct over either the shortcut list or the
+// keyspace list that implements 'Display' to render the whole file
+
+pub mod awesome;
+pub mod cheatsheet;
+pub mod dot;
+pub mod dwm;
+pub mod emacs;
+pub mod fluxbox;
+pub mod gnome;
+pub mod hyprland;
+pub mod i3;
+pub mod json;
+pub mod kde;
+pub mod keyd;
+pub mod niri;
+pub mod openbox;
+pub mod qtile;
+pub mod river;
+pub mod sxhkd;
+pub mod tmux;
+pub mod xmonad;
+pub mod xremap;
+
+use crate::constants::{Chord, Shortcut};
+use crate::error::StepError;
+use crate::keyspace::{Keyspace, KeyspaceList};
+use crate::parser::PermutationsGenerator;
+use std::borrow::Cow;
+
+pub enum Format {
+    Awesome,
+    Dot,
+    Dwm,
+    Emacs,
+    // With the names of the keyspaces to use a 'KeyMode' for
+    Fluxbox(Vec<String>),
+    Gnome,
+    Html,
+    Hyprland,
+    I3,
+    Json,
+    Kde,
+    KdeDesktop,
+    Keyd,
+    Markdown,
+    Niri,
+    Openbox,
+    Qtile,
+    River,
+    Sxhkd,
+    Tmux,
+    Xmonad,
+    Xremap,
+}
+
+impl Format {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "awesome" => Some(Format::Awesome),
+            "dot" | "graphviz" => Some(Format::Dot),
+            "dwm" => Some(Format::Dwm),
+            "emacs" => Some(Format::Emacs),
+            "fluxbox" => Some(Format::Fluxbox(Vec::new())),
+            "gnome" => Some(Format::Gnome),
+            "cheatsheet-html" | "html" => Some(Format::Html),
+            "hyprland" => Some(Format::Hyprland),
+            "i3" | "sway" => Some(Format::I3),
+            "json" => Some(Format::Json),
+            "kde" => Some(Format::Kde),
+            "kde-desktop" => Some(Format::KdeDesktop),
+            "cheatsheet" | "markdown" => Some(Format::Markdown),
+            "keyd" => Some(Format::Keyd),
+            "niri" => Some(Format::Niri),
+            "openbox" | "labwc" => Some(Format::Openbox),
+            "qtile" => Some(Format::Qtile),
+            "river" => Some(Format::River),
+            "sxhkd" => Some(Format::Sxhkd),
+            "tmux" => Some(Format::Tmux),
+            "xmonad" => Some(Format::Xmonad),
+            "xremap" => Some(Format::Xremap),
+            _ => None,
+        }
+    }
+
+    pub fn render<'a>(
+        &self,
+        generator: &PermutationsGenerator<'a>,
+        source: &str,
+    ) -> Result<String, Vec<StepError<'a>>> {
+        match self {
+            Format::Awesome => {
+                Ok(awesome::Awesome(&generator.allocate_keyspace_list()?).to_string())
+            }
+            Format::Dot => Ok(dot::Dot(&generator.allocate_keyspace_list()?).to_string()),
+            Format::Dwm => {
+                let list = generator.allocate_shortcut_list()?;
+                dwm::check(&list)?;
+                Ok(dwm::Dwm(&list).to_string())
+            }
+            Format::Emacs => Ok(emacs::Emacs(&generator.allocate_keyspace_list()?).to_string()),
+            Format::Fluxbox(keymodes) => {
+                Ok(fluxbox::Fluxbox(&generator.allocate_keyspace_list()?, keymodes).to_string())
+            }
+            Format::Gnome => {
+                let list = generator.allocate_shortcut_list()?;
+                check_single_chords(&list, "GNOME")?;
+                Ok(gnome::Gnome(&list).to_string())
+            }
+            Format::Html => Ok(cheatsheet::Html(
+                &generator.allocate_shortcut_list()?,
+                generator.comments(),
+            )
+            .to_string()),
+            Format::Hyprland => {
+                Ok(hyprland::Hyprland(&generator.allocate_keyspace_list()?).to_string())
+            }
+            Format::I3 => Ok(i3::I3(&generator.allocate_keyspace_list()?).to_string()),
+            Format::Json => Ok(json::Json(
+                source,
+                &generator.allocate_shortcut_list()?,
+                &generator.allocate_keyspace_list()?,
+            )
+            .to_string()),
+            Format::Kde => {
+                let list = generator.allocate_shortcut_list()?;
+                check_single_chords(&list, "KDE")?;
+                Ok(kde::Kde(&list).to_string())
+            }
+            Format::KdeDesktop => {
+                let list = generator.allocate_shortcut_list()?;
+                check_single_chords(&list, "KDE")?;
+                Ok(kde::KdeDesktop(&list).to_string())
+            }
+            Format::Keyd => {
+                keyd::check(&generator.allocate_shortcut_list()?)?;
+                Ok(keyd::Keyd(&generator.allocate_keyspace_list()?).to_string())
+            }
+            Format::Markdown => Ok(cheatsheet::Markdown(
+                &generator.allocate_shortcut_list()?,
+                generator.comments(),
+            )
+            .to_string()),
+            Format::Niri => {
+                let list = generator.allocate_shortcut_list()?;
+                check_single_chords(&list, "niri")?;
+                Ok(niri::Niri(&list).to_string())
+            }
+            Format::Openbox => {
+                Ok(openbox::Openbox(&generator.allocate_keyspace_list()?).to_string())
+            }
+            Format::Qtile => Ok(qtile::Qtile(&generator.allocate_keyspace_list()?).to_string()),
+            Format::River => Ok(river::River(&generator.allocate_keyspace_list()?).to_string()),
+            Format::Sxhkd => Ok(sxhkd::Sxhkd(&generator.allocate_shortcut_list()?).to_string()),
+            Format::Tmux => {
+                tmux::check(&generator.allocate_shortcut_list()?)?;
+                Ok(tmux::Tmux(&generator.allocate_keyspace_list()?).to_string())
+            }
+            Format::Xmonad => Ok(xmonad::Xmonad(&generator.allocate_shortcut_list()?).to_string()),
+            Format::Xremap => Ok(xremap::Xremap(&generator.allocate_keyspace_list()?).to_string()),
+        }
+    }
+}
+
+pub fn join_action(action: &[Cow<str>]) -> String {
+    action.join("")
+}
+
+// For targets without chains, reports every hotkey that is a chain
+pub fn check_single_chords<'a>(
+    list: &[Shortcut<'a, '_>],
+    target: &str,
+) -> Result<(), Vec<StepError<'a>>> {
+    let errors = list
+        .iter()
+        .filter(|shortcut| shortcut.hotkey.0.len() > 1)
+        .map(|shortcut| {
+            StepError::new(
+                format!(
+                    "{} is a chain of {} chords, but {} only binds single chords",
+                    shortcut.hotkey,
+                    shortcut.hotkey.0.len(),
+                    target
+                ),
+                shortcut.head,
+            )
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// For walking the keyspace list as a tree, i.e. following a 'SetState'
+pub fn find_keyspace<'x, 'a, 'b>(
+    keyspace_list: &'x [Keyspace<'a, 'b>],
+    title: &[Chord],
+) -> &'x Keyspace<'a, 'b> {
+    keyspace_list
+        .iter()
+        .find(|keyspace| keyspace.title.0 == title)
+        .expect("DEV: Every 'SetState' should have a 'Keyspace'")
+}
+
+// A name for a keyspace using only [a-z0-9_], e.g. for mode names
+// 'Super + Space ; Ctrl + a' becomes 'super_space__ctrl_a'
+pub fn identifier(title: &[Chord]) -> String {
+    let chords = title
+        .iter()
+        .map(|chord| {
+            let mut parts = chord
+                .modifier_list()
+                .map(|modifier| format!("{:?}", modifier).to_lowercase())
+                .collect::<Vec<_>>();
+            parts.push(chord.key.as_str().to_lowercase());
+            parts.join("_")
+        })
+        .collect::<Vec<_>>();
+    chords.join("__")
+}
+
+// How 'quote_string()' writes ASCII control characters other than '\n' and
+// '\t', which differs by target
+#[derive(Clone, Copy, PartialEq)]
+pub enum ControlEscape {
+    Octal,         // '\001', e.g. C and Emacs Lisp
+    Decimal,       // '\001', Lua
+    Haskell,       // '\1', followed by '\&' when a digit comes next
+    Hex,           // '\x01', Python
+    Unicode,       // '\u0001', e.g. JSON and GVariant
+    UnicodeBraced, // '\u{1}', KDL
+    Verbatim,      // As is, as DOT has no escapes for them ('\t' too)
+}
+
+// A string literal in 'quote's with backslash escapes, which most targets
+// share apart from how they write control characters
+pub fn quote_string(s: &str, quote: char, control: ControlEscape) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push(quote);
+    let mut chars = s.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            c if c == quote || c == '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\t' if control != ControlEscape::Verbatim => escaped.push_str("\\t"),
+            c if c.is_ascii_control() => {
+                let byte = c as u8;
+                match control {
+                    ControlEscape::Octal => escaped.push_str(&format!("\\{:03o}", byte)),
+                    ControlEscape::Decimal => escaped.push_str(&format!("\\{:03}", byte)),
+                    ControlEscape::Haskell => {
+                        escaped.push_str(&format!("\\{}", byte));
+                        if chars.peek().is_some_and(char::is_ascii_digit) {
+                            escaped.push_str("\\&");
+                        }
+                    }
+                    ControlEscape::Hex => escaped.push_str(&format!("\\x{:02x}", byte)),
+                    ControlEscape::Unicode => escaped.push_str(&format!("\\u{:04x}", byte)),
+                    ControlEscape::UnicodeBraced => escaped.push_str(&format!("\\u{{{:x}}}", byte)),
+                    ControlEscape::Verbatim => escaped.push(c),
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped.push(quote);
+    escaped
+}
+
+// Quotes for POSIX shells, e.g. for 'sh -c'
+pub fn sh_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', "'\\''"))
+}
+
+// Most targets only take a single line as the command to run, but a body can
+// span several lines. A multi-line body is made single-line by having the
+// shell reconstruct it with printf instead
+pub fn shell_one_line(body: &str) -> Cow<'_, str> {
+    if body.contains('\n') {
+        let mut format = String::with_capacity(body.len());
+        for ch in body.chars() {
+            match ch {
+                '\n' => format.push_str("\\n"),
+                '\\' => format.push_str("\\\\"),
+                '%' => format.push_str("%%"),
+                '\'' => format.push_str("'\\''"),
+                c => format.push(c),
+            }
+        }
+        format!("eval \"$(printf '{}')\"", format).into()
+    } else {
+        body.into()
+    }
+}
+
+// For tests, panics on any config errors
+#[cfg(test)]
+pub fn render_str(format: Format, source: &str) -> String {
+    use crate::parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+    format.render(&generator, source).unwrap()
+}
+
+#[test]
+fn multi_line_bodies() {
+    assert_eq!(shell_one_line("echo 'a'"), "echo 'a'");
+    assert_eq!(
+        shell_one_line("echo '%s'\necho \\"),
+        r#"eval "$(printf 'echo '\''%%s'\''\necho \\')""#
+    );
+}
+
+#[test]
+fn quoted_strings() {
+    let s = "say \"hi\"\t\\\u{1}2";
+    assert_eq!(
+        quote_string(s, '"', ControlEscape::Octal),
+        r#""say \"hi\"\t\\\0012""#
+    );
+    assert_eq!(
+        quote_string(s, '"', ControlEscape::Haskell),
+        r#""say \"hi\"\t\\\1\&2""#
+    );
+    assert_eq!(
+        quote_string(s, '"', ControlEscape::UnicodeBraced),
+        r#""say \"hi\"\t\\\u{1}2""#
+    );
+    assert_eq!(
+        quote_string("it's", '\'', ControlEscape::Unicode),
+        r#"'it\'s'"#
+    );
+    assert_eq!(
+        quote_string("a\tb\nc", '"', ControlEscape::Verbatim),
+        "\"a\tb\\nc\""
+    );
+}
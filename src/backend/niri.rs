@@ -0,0 +1,78 @@
+// niri, as the KDL 'binds' node
+
+use super::{join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use std::fmt;
+
+pub struct Niri<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+impl fmt::Display for Niri<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "binds {{")?;
+        for shortcut in self.0 {
+            let argv = argv(&join_action(shortcut.action))
+                .iter()
+                .map(|arg| quote_string(arg, '"', ControlEscape::UnicodeBraced))
+                .collect::<Vec<_>>();
+            writeln!(
+                f,
+                "    {} {{ spawn {}; }}",
+                NiriChord(&shortcut.hotkey.0[0]),
+                argv.join(" ")
+            )?;
+        }
+        writeln!(f, "}}")
+    }
+}
+
+// niri runs 'spawn' without a shell, so only split simple bodies into words
+fn argv(body: &str) -> Vec<&str> {
+    let is_plain = |c: char| c.is_alphanumeric() || "-_./=:,+@%".contains(c);
+    if body.trim().is_empty() {
+        vec!["true"]
+    } else if body.chars().all(|c| c == ' ' || is_plain(c)) {
+        body.split_whitespace().collect()
+    } else {
+        vec!["sh", "-c", body]
+    }
+}
+
+// The 'Mod+Key' node name
+struct NiriChord<'c>(&'c Chord);
+
+impl fmt::Display for NiriChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "Shift",
+                Mod::Super => "Super",
+                Mod::Ctrl => "Ctrl",
+                Mod::Alt => "Alt",
+            };
+            write!(f, "{}+", name)?;
+        }
+        match self.0.key {
+            Key::Space | Key::Return | Key::Comma => f.write_str(self.0.key.as_str()),
+            ref key => f.write_str(&key.as_str().to_uppercase()),
+        }
+    }
+}
+
+#[test]
+fn niri_binds() {
+    use super::{render_str, Format};
+    let source = r#"
+|super t| alacritty --class=term
+|super shift e| niri msg action quit && echo "bye"
+|ctrl alt Return|
+"#;
+    assert_eq!(
+        render_str(Format::Niri, source),
+        r#"binds {
+    Shift+Super+E { spawn "sh" "-c" "niri msg action quit && echo \"bye\""; }
+    Super+T { spawn "alacritty" "--class=term"; }
+    Ctrl+Alt+Return { spawn "true"; }
+}
+"#
+    );
+}
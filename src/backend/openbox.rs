@@ -0,0 +1,135 @@
+// Openbox and labwc 'rc.xml', which nest chains as '<keybind>' elements
+//
+// Outputs the fragment that goes inside of '<keyboard>'. Keyspaces are
+// nested following each 'SetState' from the root keyspace
+
+use super::{find_keyspace, join_action, sh_quote};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Openbox<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for Openbox<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(root) = self.0.first() {
+            self.write_keyspace(f, root, 0)?;
+        }
+        Ok(())
+    }
+}
+
+impl Openbox<'_, '_, '_> {
+    fn write_keyspace(
+        &self,
+        f: &mut fmt::Formatter,
+        keyspace: &Keyspace,
+        depth: usize,
+    ) -> fmt::Result {
+        let padding = "  ".repeat(depth);
+        for (chord, action) in &keyspace.list {
+            match action {
+                KeyspaceAction::SetState(chord_list) => {
+                    writeln!(f, "{}<keybind key=\"{}\">", padding, OpenboxChord(chord))?;
+                    self.write_keyspace(f, find_keyspace(self.0, chord_list), depth + 1)?;
+                    // Leaving a chain should not require an unbound key
+                    writeln!(f, "{}  <keybind key=\"Escape\">", padding)?;
+                    writeln!(f, "{}    <action name=\"BreakChroot\"/>", padding)?;
+                    writeln!(f, "{}  </keybind>", padding)?;
+                    writeln!(f, "{}</keybind>", padding)?;
+                }
+                KeyspaceAction::Action(action) => {
+                    let body = join_action(action);
+                    if body.is_empty() {
+                        writeln!(f, "{}<keybind key=\"{}\"/>", padding, OpenboxChord(chord))?;
+                        continue;
+                    }
+                    // Openbox does not run 'command' through a shell
+                    let command = format!("sh -c {}", sh_quote(&body));
+                    writeln!(f, "{}<keybind key=\"{}\">", padding, OpenboxChord(chord))?;
+                    writeln!(f, "{}  <action name=\"Execute\">", padding)?;
+                    writeln!(
+                        f,
+                        "{}    <command>{}</command>",
+                        padding,
+                        xml_escape(&command)
+                    )?;
+                    writeln!(f, "{}  </action>", padding)?;
+                    writeln!(f, "{}</keybind>", padding)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct OpenboxChord<'c>(&'c Chord);
+
+impl fmt::Display for OpenboxChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "S",
+                Mod::Super => "W",
+                Mod::Ctrl => "C",
+                Mod::Alt => "A",
+            };
+            write!(f, "{}-", name)?;
+        }
+        let key = match self.0.key {
+            Key::Space => "space",
+            Key::Comma => "comma",
+            ref key => key.as_str(),
+        };
+        f.write_str(key)
+    }
+}
+
+// Only for text content, keys never need escaping in attributes
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[test]
+fn openbox_nested_chains() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| nmtui && echo "<done>"
+|super Space; ctrl Space; b|
+|alt Return| st
+"#;
+    assert_eq!(
+        render_str(Format::Openbox, source),
+        r#"<keybind key="W-space">
+  <keybind key="C-space">
+    <keybind key="b"/>
+    <keybind key="Escape">
+      <action name="BreakChroot"/>
+    </keybind>
+  </keybind>
+  <keybind key="W-w">
+    <action name="Execute">
+      <command>sh -c 'nmtui &amp;&amp; echo "&lt;done&gt;"'</command>
+    </action>
+  </keybind>
+  <keybind key="Escape">
+    <action name="BreakChroot"/>
+  </keybind>
+</keybind>
+<keybind key="A-Return">
+  <action name="Execute">
+    <command>sh -c 'st'</command>
+  </action>
+</keybind>
+"#
+    );
+}
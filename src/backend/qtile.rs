@@ -0,0 +1,114 @@
+// Qtile, as a 'keys.py' module with a 'keys' list for the user's config
+//
+// A 'KeyChord' holds the bindings of the next keyspace directly, so the
+// keyspaces are nested following each 'SetState' from the root keyspace
+
+use super::{find_keyspace, join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Hotkey, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Qtile<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for Qtile<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "from libqtile.config import Key, KeyChord")?;
+        writeln!(f, "from libqtile.lazy import lazy")?;
+        writeln!(f)?;
+        writeln!(f, "keys = [")?;
+        if let Some(root) = self.0.first() {
+            self.write_keyspace(f, root, 1)?;
+        }
+        writeln!(f, "]")
+    }
+}
+
+impl Qtile<'_, '_, '_> {
+    fn write_keyspace(
+        &self,
+        f: &mut fmt::Formatter,
+        keyspace: &Keyspace,
+        depth: usize,
+    ) -> fmt::Result {
+        let padding = "    ".repeat(depth);
+        for (chord, action) in &keyspace.list {
+            match action {
+                KeyspaceAction::SetState(chord_list) => {
+                    writeln!(f, "{}KeyChord({}, [", padding, QtileChord(chord))?;
+                    self.write_keyspace(f, find_keyspace(self.0, chord_list), depth + 1)?;
+                    writeln!(
+                        f,
+                        "{}], name={}),",
+                        padding,
+                        quote_string(&Hotkey(chord_list).to_string(), '"', ControlEscape::Hex)
+                    )?;
+                }
+                KeyspaceAction::Action(action) => {
+                    let body = join_action(action);
+                    if body.is_empty() {
+                        writeln!(f, "{}Key({}),", padding, QtileChord(chord))?;
+                    } else {
+                        writeln!(
+                            f,
+                            "{}Key({}, lazy.spawn({}, shell=True)),",
+                            padding,
+                            QtileChord(chord),
+                            quote_string(&body, '"', ControlEscape::Hex)
+                        )?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// The '[modifiers], key' part of 'Key' and 'KeyChord'
+struct QtileChord<'c>(&'c Chord);
+
+impl fmt::Display for QtileChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = self
+            .0
+            .modifier_list()
+            .map(|modifier| match modifier {
+                Mod::Shift => "\"shift\"",
+                Mod::Super => "\"mod4\"",
+                Mod::Ctrl => "\"control\"",
+                Mod::Alt => "\"mod1\"",
+            })
+            .collect::<Vec<_>>();
+        let key = match self.0.key {
+            Key::Space => "space",
+            Key::Comma => "comma",
+            ref key => key.as_str(),
+        };
+        write!(f, "[{}], \"{}\"", modifiers.join(", "), key)
+    }
+}
+
+#[test]
+fn qtile_key_chords() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| $TERMINAL -e sh -c "nmtui"
+|super Space; ctrl Space; b|
+|alt shift Return| printf '%s\n' st
+"#;
+    assert_eq!(
+        render_str(Format::Qtile, source),
+        r#"from libqtile.config import Key, KeyChord
+from libqtile.lazy import lazy
+
+keys = [
+    KeyChord(["mod4"], "space", [
+        KeyChord(["control"], "space", [
+            Key([], "b"),
+        ], name="Super + Space ; Ctrl + Space"),
+        Key(["mod4"], "w", lazy.spawn("$TERMINAL -e sh -c \"nmtui\"", shell=True)),
+    ], name="Super + Space"),
+    Key(["shift", "mod1"], "Return", lazy.spawn("printf '%s\\n' st", shell=True)),
+]
+"#
+    );
+}
@@ -0,0 +1,115 @@
+// river, as 'riverctl' commands for the 'init' script
+//
+// The root keyspace maps into river's "normal" mode and every other keyspace
+// is declared as its own mode. A mapping runs a single command, so an action
+// in a mode leaves it from within the spawned shell
+
+use super::{identifier, join_action, sh_quote};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct River<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for River<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for keyspace in self.0.iter().skip(1) {
+            writeln!(f, "riverctl declare-mode {}", identifier(keyspace.title.0))?;
+        }
+
+        for keyspace in self.0 {
+            let is_root = keyspace.title.0.is_empty();
+            let mode = if is_root {
+                "normal".into()
+            } else {
+                identifier(keyspace.title.0)
+            };
+            // Only separates from the 'declare-mode's
+            if self.0.len() > 1 {
+                writeln!(f)?;
+            }
+
+            for (chord, action) in &keyspace.list {
+                write!(f, "riverctl map {} {} ", mode, RiverChord(chord))?;
+                match action {
+                    KeyspaceAction::SetState(chord_list) => {
+                        writeln!(f, "enter-mode {}", identifier(chord_list))?;
+                    }
+                    KeyspaceAction::Action(action) => {
+                        let body = join_action(action);
+                        match (is_root, body.is_empty()) {
+                            (true, true) => writeln!(f, "spawn true")?,
+                            (true, false) => writeln!(f, "spawn {}", sh_quote(&body))?,
+                            (false, true) => writeln!(f, "enter-mode normal")?,
+                            (false, false) => writeln!(
+                                f,
+                                "spawn {}",
+                                sh_quote(&format!("riverctl enter-mode normal\n{}", body))
+                            )?,
+                        }
+                    }
+                }
+            }
+
+            if !is_root {
+                writeln!(f, "riverctl map {} None Escape enter-mode normal", mode)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The 'modifiers key' part of 'riverctl map'
+struct RiverChord<'c>(&'c Chord);
+
+impl fmt::Display for RiverChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let modifiers = self
+            .0
+            .modifier_list()
+            .map(|modifier| match modifier {
+                Mod::Shift => "Shift",
+                Mod::Super => "Super",
+                Mod::Ctrl => "Control",
+                Mod::Alt => "Alt",
+            })
+            .collect::<Vec<_>>();
+        if modifiers.is_empty() {
+            f.write_str("None ")?;
+        } else {
+            write!(f, "{} ", modifiers.join("+"))?;
+        }
+        match self.0.key {
+            Key::Space | Key::Return => f.write_str(self.0.key.as_str()),
+            Key::Comma => f.write_str("comma"),
+            ref key => f.write_str(&key.as_str().to_uppercase()),
+        }
+    }
+}
+
+#[test]
+fn river_modes() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| nmtui && echo "it's done"
+|super Space; ctrl Space; b|
+|super shift Return| foot
+"#;
+    assert_eq!(
+        render_str(Format::River, source),
+        r#"riverctl declare-mode super_space
+riverctl declare-mode super_space__ctrl_space
+
+riverctl map normal Super Space enter-mode super_space
+riverctl map normal Shift+Super Return spawn 'foot'
+
+riverctl map super_space Control Space enter-mode super_space__ctrl_space
+riverctl map super_space Super W spawn 'riverctl enter-mode normal
+nmtui && echo "it'\''s done"'
+riverctl map super_space None Escape enter-mode normal
+
+riverctl map super_space__ctrl_space None B enter-mode normal
+riverctl map super_space__ctrl_space None Escape enter-mode normal
+"#
+    );
+}
@@ -0,0 +1,259 @@
+// sxhkd, which supports chord chains natively so no keyspaces are needed
+//
+// Shortcuts expanded from the same entry that only differ in one chord are
+// collapsed back into a single hotkey using sxhkd's own '{a,b}' sequences
+// (when the bodies can be written as a sequence too)
+
+use super::{join_action, shell_one_line};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use std::fmt;
+
+pub struct Sxhkd<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+impl fmt::Display for Sxhkd<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let list = self.0;
+        let mut done = vec![false; list.len()];
+        for i in 0..list.len() {
+            if done[i] {
+                continue;
+            }
+
+            // Find the siblings that only vary in the same chord as 'list[i]'
+            let mut group = vec![i];
+            let mut varying = None;
+            for j in i + 1..list.len() {
+                if done[j] || !std::ptr::eq(list[i].head, list[j].head) {
+                    continue;
+                }
+                match (varying, differ_at(list[i].hotkey.0, list[j].hotkey.0)) {
+                    (None, Some(k)) => {
+                        varying = Some(k);
+                        group.push(j);
+                    }
+                    (Some(k), Some(l)) if k == l => group.push(j),
+                    _ => {}
+                }
+            }
+
+            let siblings = group.iter().map(|j| &list[*j]).collect::<Vec<_>>();
+            let collapsed = varying.and_then(|k| {
+                let actions = siblings
+                    .iter()
+                    .map(|shortcut| join_action(shortcut.action))
+                    .collect::<Vec<_>>();
+                collapse_actions(&actions).map(|body| (k, body))
+            });
+
+            if let Some((k, body)) = collapsed {
+                group.iter().for_each(|j| done[*j] = true);
+                let chords = siblings[0].hotkey.0;
+                let varying_chords = siblings
+                    .iter()
+                    .map(|shortcut| &shortcut.hotkey.0[k])
+                    .collect::<Vec<_>>();
+                for (index, chord) in chords.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" ; ")?;
+                    }
+                    if index == k {
+                        write_chord_sequence(f, &varying_chords)?;
+                    } else {
+                        write!(f, "{}", SxhkdChord(chord))?;
+                    }
+                }
+                writeln!(f, "\n\t{}\n", body)?;
+            } else {
+                done[i] = true;
+                for (index, chord) in list[i].hotkey.0.iter().enumerate() {
+                    if index > 0 {
+                        f.write_str(" ; ")?;
+                    }
+                    write!(f, "{}", SxhkdChord(chord))?;
+                }
+                writeln!(f, "\n\t{}\n", body(&join_action(list[i].action)))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// The index of the only chord that differs between the two
+fn differ_at(first: &[Chord], second: &[Chord]) -> Option<usize> {
+    if first.len() != second.len() {
+        return None;
+    }
+    let mut differences = first
+        .iter()
+        .zip(second)
+        .enumerate()
+        .filter(|(_, (a, b))| a != b)
+        .map(|(i, _)| i);
+    match (differences.next(), differences.next()) {
+        (Some(i), None) => Some(i),
+        _ => None,
+    }
+}
+
+// 'super + {a,b}' when only the key varies, '{super + a,alt + b}' otherwise
+fn write_chord_sequence(f: &mut fmt::Formatter, chords: &[&Chord]) -> fmt::Result {
+    let modifiers = chords[0].modifiers;
+    if chords.iter().all(|chord| chord.modifiers == modifiers) {
+        for modifier in chords[0].modifier_list() {
+            write!(f, "{} + ", mod_name(modifier))?;
+        }
+        let keys = chords
+            .iter()
+            .map(|chord| key_name(&chord.key))
+            .collect::<Vec<_>>();
+        write!(f, "{{{}}}", keys.join(","))
+    } else {
+        let chords = chords
+            .iter()
+            .map(|chord| SxhkdChord(chord).to_string())
+            .collect::<Vec<_>>();
+        write!(f, "{{{}}}", chords.join(","))
+    }
+}
+
+// Writes the varying middle of 'actions' as a sequence, e.g. 'echo {a,b}',
+// or the one body when they are all the same
+// None if it cannot be expressed as a sequence
+fn collapse_actions(actions: &[String]) -> Option<String> {
+    let first = actions[0].as_str();
+    if actions.iter().all(|action| action == first) {
+        return Some(body(first));
+    }
+    if actions.iter().any(|action| action.contains('\n')) {
+        return None;
+    }
+
+    let prefix_len = actions[1..].iter().fold(first.len(), |len, action| {
+        first[..len]
+            .char_indices()
+            .zip(action.chars())
+            .find(|((_, a), b)| a != b)
+            .map(|((i, _), _)| i)
+            .unwrap_or_else(|| std::cmp::min(len, action.len()))
+    });
+    let suffix_len = actions
+        .iter()
+        .fold(first.len() - prefix_len, |len, action| {
+            let rest = &action[prefix_len..];
+            let tail = &first[first.len() - len..];
+            tail.chars()
+                .rev()
+                .zip(rest.chars().rev())
+                .take_while(|(a, b)| a == b)
+                .map(|(a, _)| a.len_utf8())
+                .sum::<usize>()
+                .min(rest.len())
+        });
+
+    // Do not split words, 'echo {a1,a2}' reads better than 'echo a{1,2}'
+    let is_word = |c: char| c.is_alphanumeric() || "_-.".contains(c);
+    let prefix_len = first[..prefix_len].trim_end_matches(is_word).len();
+    let suffix_len = first[first.len() - suffix_len..]
+        .trim_start_matches(is_word)
+        .len();
+
+    let mut middles = Vec::with_capacity(actions.len());
+    for action in actions {
+        let middle = &action[prefix_len..action.len() - suffix_len];
+        if middle == "_" || middle.contains(|c| "{},\\".contains(c)) {
+            return None;
+        }
+        middles.push(if middle.is_empty() { "_" } else { middle });
+    }
+    Some(format!(
+        "{}{{{}}}{}",
+        escape(&first[..prefix_len]),
+        middles.join(","),
+        escape(&first[first.len() - suffix_len..]),
+    ))
+}
+
+fn body(action: &str) -> String {
+    if action.is_empty() {
+        return ":".into();
+    }
+    // A body that is already continued shell-style maps directly onto
+    // sxhkd's line-continuation, otherwise it has to become a single line
+    let lines = action.lines().collect::<Vec<_>>();
+    let (last, rest) = lines.split_last().unwrap();
+    if rest.iter().all(|line| line.ends_with('\\')) {
+        let mut output = String::with_capacity(action.len());
+        for line in rest {
+            output.push_str(&escape(line));
+            output.push_str("\n\t");
+        }
+        output.push_str(&escape(last));
+        output
+    } else {
+        escape(&shell_one_line(action))
+    }
+}
+
+// Braces are sequences in sxhkd so literal ones need escaping
+fn escape(s: &str) -> String {
+    s.replace('{', "\\{").replace('}', "\\}")
+}
+
+struct SxhkdChord<'c>(&'c Chord);
+
+impl fmt::Display for SxhkdChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            write!(f, "{} + ", mod_name(modifier))?;
+        }
+        f.write_str(key_name(&self.0.key))
+    }
+}
+
+fn mod_name(modifier: Mod) -> &'static str {
+    match modifier {
+        Mod::Shift => "shift",
+        Mod::Super => "super",
+        Mod::Ctrl => "ctrl",
+        Mod::Alt => "alt",
+    }
+}
+
+fn key_name(key: &Key) -> &'static str {
+    match key {
+        Key::Space => "space",
+        Key::Comma => "comma",
+        key => key.as_str(),
+    }
+}
+
+#[test]
+fn sxhkd_sequences() {
+    use super::{render_str, Format};
+    let source = r#"
+|super {{a,b}}| echo {{1,2}}
+|super {{, alt}} Return| {{st,sakura}} -e "${SHELL}"
+|super Space; super {{m,n}}| open '{{mw.sh,}}'
+|super Space; super {{e,w}}| same
+|super q|
+"#;
+    assert_eq!(
+        render_str(Format::Sxhkd, source),
+        r#"super + space ; super + {e,w}
+	same
+
+super + space ; super + {m,n}
+	open '{mw.sh,_}'
+
+super + {a,b}
+	echo {1,2}
+
+super + q
+	:
+
+{super + Return,super + alt + Return}
+	{st,sakura} -e "$\{SHELL\}"
+
+"#
+    );
+}
@@ -0,0 +1,131 @@
+// tmux, where each keyspace other than the root becomes a key table
+//
+// A key table only lasts for the next key press, so there is no need to
+// switch back to the root table after an action
+
+use super::{identifier, join_action, sh_quote, shell_one_line};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use crate::error::StepError;
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Tmux<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+// The terminal never sends super to tmux
+pub fn check<'a>(list: &[Shortcut<'a, '_>]) -> Result<(), Vec<StepError<'a>>> {
+    let errors = list
+        .iter()
+        .filter(|shortcut| {
+            shortcut.hotkey.0.iter().any(|chord| {
+                chord
+                    .modifier_list()
+                    .any(|modifier| matches!(modifier, Mod::Super))
+            })
+        })
+        .map(|shortcut| {
+            StepError::new(
+                format!("{} uses super, which tmux cannot bind", shortcut.hotkey),
+                shortcut.head,
+            )
+        })
+        .collect::<Vec<_>>();
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl fmt::Display for Tmux<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for keyspace in self.0 {
+            let table = if keyspace.title.0.is_empty() {
+                "-n".into()
+            } else {
+                writeln!(f)?;
+                format!("-T {}", identifier(keyspace.title.0))
+            };
+
+            for (chord, action) in &keyspace.list {
+                write!(f, "bind-key {} {} ", table, TmuxChord(chord))?;
+                match action {
+                    KeyspaceAction::SetState(chord_list) => {
+                        writeln!(f, "switch-client -T {}", identifier(chord_list))?;
+                    }
+                    KeyspaceAction::Action(action) => {
+                        writeln!(f, "{}", command(&join_action(action)))?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// A body of 'tmux <command>' is run as the tmux command directly, if it does
+// not need a shell. Otherwise it is run with 'run-shell'
+fn command(body: &str) -> String {
+    let needs_shell = |s: &str| s.contains(|c| "\n;|&$`<>(){}#\\".contains(c));
+    match body.strip_prefix("tmux ") {
+        Some(tmux_command) if !needs_shell(tmux_command) => tmux_command.trim().into(),
+        _ if body.is_empty() => "run-shell -b true".into(),
+        // Formats in 'run-shell' are expanded, '##' is a literal '#'
+        _ => format!(
+            "run-shell -b {}",
+            sh_quote(&shell_one_line(body)).replace('#', "##")
+        ),
+    }
+}
+
+struct TmuxChord<'c>(&'c Chord);
+
+impl fmt::Display for TmuxChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut shift = false;
+        for modifier in self.0.modifier_list() {
+            match modifier {
+                Mod::Shift => shift = true,
+                Mod::Super => {} // Rejected by 'check()'
+                Mod::Ctrl => f.write_str("C-")?,
+                Mod::Alt => f.write_str("M-")?,
+            }
+        }
+        let key = match self.0.key {
+            Key::Space => "Space",
+            Key::Return => "Enter",
+            Key::Comma => ",",
+            ref key => key.as_str(),
+        };
+        // tmux uses the uppercase letter for shift
+        if shift && key.len() == 1 && key.chars().all(char::is_alphabetic) {
+            f.write_str(&key.to_uppercase())
+        } else if shift {
+            write!(f, "S-{}", key)
+        } else {
+            f.write_str(key)
+        }
+    }
+}
+
+#[test]
+fn tmux_key_tables() {
+    use super::{render_str, Format};
+    let source = r#"
+|ctrl Space; c| tmux new-window -c '#{pane_current_path}'
+|ctrl Space; shift c| tmux new-window
+|ctrl Space; ctrl Space; alt 1| notify-send "it's #1"
+|alt Return| st
+"#;
+    assert_eq!(
+        render_str(Format::Tmux, source),
+        r#"bind-key -n C-Space switch-client -T ctrl_space
+bind-key -n M-Enter run-shell -b 'st'
+
+bind-key -T ctrl_space C-Space switch-client -T ctrl_space__ctrl_space
+bind-key -T ctrl_space c run-shell -b 'tmux new-window -c '\''##{pane_current_path}'\'''
+bind-key -T ctrl_space C new-window
+
+bind-key -T ctrl_space__ctrl_space M-1 run-shell -b 'notify-send "it'\''s ##1"'
+"#
+    );
+}
@@ -0,0 +1,97 @@
+// xmonad, as a Haskell module with a list for EZConfig's 'additionalKeysP'
+//
+// EZConfig turns space-separated key sequences into submaps itself, so this
+// only needs the shortcut list
+
+use super::{join_action, quote_string, ControlEscape};
+use crate::constants::{Chord, Key, Mod, Shortcut};
+use std::fmt;
+
+pub struct Xmonad<'x, 'a, 'b>(pub &'x [Shortcut<'a, 'b>]);
+
+impl fmt::Display for Xmonad<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "module Shortcuts (shortcuts) where")?;
+        writeln!(f)?;
+        writeln!(f, "import XMonad")?;
+        writeln!(f)?;
+        writeln!(f, "shortcuts :: [(String, X ())]")?;
+        if self.0.is_empty() {
+            return writeln!(f, "shortcuts = []");
+        }
+
+        writeln!(f, "shortcuts =")?;
+        for (i, shortcut) in self.0.iter().enumerate() {
+            let sequence = shortcut
+                .hotkey
+                .0
+                .iter()
+                .map(|chord| XmonadChord(chord).to_string())
+                .collect::<Vec<_>>();
+            let body = join_action(shortcut.action);
+            let action = if body.is_empty() {
+                "return ()".into()
+            } else {
+                format!("spawn {}", quote_string(&body, '"', ControlEscape::Haskell))
+            };
+            writeln!(
+                f,
+                "    {} ({}, {})",
+                if i == 0 { '[' } else { ',' },
+                quote_string(&sequence.join(" "), '"', ControlEscape::Haskell),
+                action
+            )?;
+        }
+        writeln!(f, "    ]")
+    }
+}
+
+struct XmonadChord<'c>(&'c Chord);
+
+impl fmt::Display for XmonadChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut shift = false;
+        for modifier in self.0.modifier_list() {
+            match modifier {
+                Mod::Shift => shift = true,
+                Mod::Super => f.write_str("M4-")?,
+                Mod::Ctrl => f.write_str("C-")?,
+                Mod::Alt => f.write_str("M1-")?,
+            }
+        }
+        // Only for the 'M4-S-<Return>' order that EZConfig documents
+        if shift {
+            f.write_str("S-")?;
+        }
+        match self.0.key {
+            Key::Space => f.write_str("<Space>"),
+            Key::Return => f.write_str("<Return>"),
+            Key::Comma => f.write_str(","),
+            ref key => f.write_str(key.as_str()),
+        }
+    }
+}
+
+#[test]
+fn xmonad_ezconfig() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| $TERMINAL -e sh -c "nmtui"
+|super shift Return| printf '%s\n' "\o/"
+|super shift q|
+"#;
+    assert_eq!(
+        render_str(Format::Xmonad, source),
+        r#"module Shortcuts (shortcuts) where
+
+import XMonad
+
+shortcuts :: [(String, X ())]
+shortcuts =
+    [ ("M4-<Space> M4-w", spawn "$TERMINAL -e sh -c \"nmtui\"")
+    , ("M4-S-q", return ())
+    , ("M4-S-<Return>", spawn "printf '%s\\n' \"\\o/\"")
+    ]
+"#
+    );
+}
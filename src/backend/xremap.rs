@@ -0,0 +1,103 @@
+// xremap, as the 'keymap:' section of its YAML config
+//
+// A keyspace is a nested 'remap', which xremap leaves after the next key
+// press. Keyspaces are nested following each 'SetState' from the root
+
+use super::json::json_string;
+use super::{find_keyspace, join_action};
+use crate::constants::{Chord, Key, Mod};
+use crate::keyspace::{Keyspace, KeyspaceAction};
+use std::fmt;
+
+pub struct Xremap<'x, 'a, 'b>(pub &'x [Keyspace<'a, 'b>]);
+
+impl fmt::Display for Xremap<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "keymap:")?;
+        writeln!(f, "  - name: shortcuts")?;
+        match self.0.first() {
+            Some(root) if !root.list.is_empty() => {
+                writeln!(f, "    remap:")?;
+                self.write_keyspace(f, root, 3)
+            }
+            _ => writeln!(f, "    remap: {{}}"),
+        }
+    }
+}
+
+impl Xremap<'_, '_, '_> {
+    fn write_keyspace(
+        &self,
+        f: &mut fmt::Formatter,
+        keyspace: &Keyspace,
+        depth: usize,
+    ) -> fmt::Result {
+        let padding = "  ".repeat(depth);
+        for (chord, action) in &keyspace.list {
+            writeln!(f, "{}{}:", padding, XremapChord(chord))?;
+            match action {
+                KeyspaceAction::SetState(chord_list) => {
+                    writeln!(f, "{}  remap:", padding)?;
+                    self.write_keyspace(f, find_keyspace(self.0, chord_list), depth + 2)?;
+                }
+                KeyspaceAction::Action(action) => {
+                    let body = join_action(action);
+                    let argv = if body.is_empty() {
+                        json_string("true")
+                    } else {
+                        format!("\"sh\", \"-c\", {}", json_string(&body))
+                    };
+                    writeln!(f, "{}  launch: [{}]", padding, argv)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+struct XremapChord<'c>(&'c Chord);
+
+impl fmt::Display for XremapChord<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for modifier in self.0.modifier_list() {
+            let name = match modifier {
+                Mod::Shift => "Shift-",
+                Mod::Super => "Super-",
+                Mod::Ctrl => "C-",
+                Mod::Alt => "M-",
+            };
+            f.write_str(name)?;
+        }
+        match self.0.key {
+            Key::Return => f.write_str("enter"),
+            ref key => f.write_str(&key.as_str().to_lowercase()),
+        }
+    }
+}
+
+#[test]
+fn xremap_nested_remaps() {
+    use super::{render_str, Format};
+    let source = r#"
+|super Space; super w| nmtui && echo "done"
+|super Space; ctrl Space; b|
+|alt shift Return| foot
+"#;
+    assert_eq!(
+        render_str(Format::Xremap, source),
+        r#"keymap:
+  - name: shortcuts
+    remap:
+      Super-space:
+        remap:
+          C-space:
+            remap:
+              b:
+                launch: ["true"]
+          Super-w:
+            launch: ["sh", "-c", "nmtui && echo \"done\""]
+      Shift-M-enter:
+        launch: ["sh", "-c", "foot"]
+"#
+    );
+}
@@ -6,6 +6,10 @@ use std::borrow::Cow;
 pub struct Shortcut<'a, 'b> {
     pub hotkey: Hotkey<'b>,
     pub action: &'b [Cow<'a, str>],
+    // The head of the entry in the config that this was expanded from
+    pub head: &'a str,
+    // Which member of each '{{..}}' set in 'head' was chosen
+    pub choices: &'b [usize],
 }
 
 //impl<'a, 'b> Ord for Shortcut<'a, 'b> {
@@ -45,11 +49,22 @@ impl std::fmt::Display for Chord {
                 write!(f, "{:?} + ", NUM_TO_MOD[i as usize].clone())?;
             }
         }
-        write!(f, "{}", KEYSTRS[self.key.id() as usize])
+        write!(f, "{}", KEYSTRS[self.key.id()])
         //write!(f, "{:?}", self.key)
     }
 }
 
+impl Chord {
+    // In the same order as 'Display'
+    pub fn modifier_list(&self) -> impl Iterator<Item = Mod> + '_ {
+        NUM_TO_MOD
+            .iter()
+            .enumerate()
+            .filter(move |(i, _)| self.modifiers & (1 << i) > 0)
+            .map(|(_, modifier)| modifier.clone())
+    }
+}
+
 pub type Modifiers = u16;
 
 macro_rules! enum_mod {
@@ -121,6 +136,11 @@ macro_rules! declare_keycodes {
             fn id(&self) -> usize {
                 unsafe { *(self as *const Self as *const usize) }
             }
+
+            // The name used in the config file
+            pub fn as_str(&self) -> &'static str {
+                KEYSTRS[self.id()]
+            }
         }
 
         // TODO: replace this with phf::Map?
@@ -0,0 +1,114 @@
+use std::path::Path;
+
+// An error that points back into the config file
+//
+// 'span' is always a subslice of the text that was read from the config
+// file, so the row and column are recovered with pointer arithmetic at the
+// time of rendering rather than tracked through every pass
+#[derive(Debug)]
+pub struct StepError<'a> {
+    pub message: String,
+    pub span: &'a str,
+    // Secondary locations, e.g. where the other half of a conflict is
+    pub notes: Vec<(String, &'a str)>,
+}
+
+impl<'a> StepError<'a> {
+    pub fn new<S: Into<String>>(message: S, span: &'a str) -> Self {
+        Self {
+            message: message.into(),
+            span,
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_note<S: Into<String>>(mut self, message: S, span: &'a str) -> Self {
+        self.notes.push((message.into(), span));
+        self
+    }
+
+    // Renders similar to rustc
+    //
+    // error: Key "foo" not found
+    //  --> config.txt:3:8
+    //   |
+    // 3 | |super foo| echo
+    //   |        ^^^
+    pub fn render(&self, path: &Path, source: &str) -> String {
+        let mut output = render_snippet("error", &self.message, self.span, path, source);
+        for (message, span) in &self.notes {
+            output.push_str(&render_snippet("note", message, span, path, source));
+        }
+        output
+    }
+}
+
+fn render_snippet(level: &str, message: &str, span: &str, path: &Path, source: &str) -> String {
+    let (row, col, line) = locate(source, span);
+    let row_str = row.to_string();
+    let gutter = " ".repeat(row_str.len());
+
+    // Tabs are kept so that the caret lines up with the source line
+    let padding = line
+        .chars()
+        .take(col - 1)
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let span_on_line = span.lines().next().unwrap_or("");
+    let width = std::cmp::max(span_on_line.chars().count(), 1);
+
+    format!(
+        "{}: {}\n{} --> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+        level,
+        message,
+        gutter,
+        path.display(),
+        row,
+        col,
+        gutter,
+        row_str,
+        line,
+        gutter,
+        padding,
+        "^".repeat(width),
+    )
+}
+
+// Returns the (row, column, line) of the start of 'span' within 'source'
+// Both row and column are 1-indexed, column is counted in chars
+pub fn locate<'a>(source: &'a str, span: &str) -> (usize, usize, &'a str) {
+    let base = source.as_ptr() as usize;
+    let offset = span.as_ptr() as usize - base;
+    debug_assert!(offset <= source.len(), "'span' is not within 'source'");
+
+    let line_start = source[..offset].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_close = source[offset..]
+        .find('\n')
+        .map(|i| offset + i)
+        .unwrap_or_else(|| source.len());
+    let row = source[..line_start].matches('\n').count() + 1;
+    let col = source[line_start..offset].chars().count() + 1;
+    let line = source[line_start..line_close].trim_end_matches('\r');
+    (row, col, line)
+}
+
+#[test]
+fn render_points_at_span() {
+    let source = "#hello\n|super foo| echo\n";
+    let start = source.find("foo").unwrap();
+    let err = StepError::new("Key \"foo\" not found", &source[start..start + 3]);
+    assert_eq!(
+        err.render(Path::new("config.txt"), source),
+        concat!(
+            "error: Key \"foo\" not found\n",
+            "  --> config.txt:2:8\n",
+            "  |\n",
+            "2 | |super foo| echo\n",
+            "  |        ^^^\n",
+        )
+    );
+
+    // Empty spans (e.g. end of file) still get a caret
+    let err = StepError::new("end", &source[source.len()..]);
+    assert!(err.render(Path::new("c"), source).contains("3:1"));
+}
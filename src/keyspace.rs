@@ -1,4 +1,5 @@
 use crate::constants::{Chord, Hotkey, Shortcut};
+use crate::error::StepError;
 use crate::parser::PermutationsGenerator;
 use std::borrow::Cow;
 
@@ -21,7 +22,8 @@ pub struct Keyspace<'a, 'b> {
 
 // As a 'trait' rather than direct 'impl' so we import explicitly
 pub trait KeyspaceList<'a> {
-    fn allocate_keyspace_list<'b>(&'b self) -> Result<Vec<Keyspace<'a, 'b>>, String>;
+    fn allocate_keyspace_list<'b>(&'b self)
+        -> Result<Vec<Keyspace<'a, 'b>>, Vec<StepError<'a>>>;
 }
 
 // This a means of providing window managers that do not support chaining
@@ -39,7 +41,9 @@ pub trait KeyspaceList<'a> {
 // 'VisitedTracker' which is just a (usize, usize) of the same length as
 // the shortcut_list to track the traversal of the shortcut list
 impl<'a> KeyspaceList<'a> for PermutationsGenerator<'a> {
-    fn allocate_keyspace_list<'b>(&'b self) -> Result<Vec<Keyspace<'a, 'b>>, String> {
+    fn allocate_keyspace_list<'b>(
+        &'b self,
+    ) -> Result<Vec<Keyspace<'a, 'b>>, Vec<StepError<'a>>> {
         let shortcut_list = self.allocate_shortcut_list()?;
         let mut keyspace_list = Vec::new();
         let mut visited = VisitedTracker::new(shortcut_list.as_slice());
@@ -83,7 +87,7 @@ impl<'a, 'b> Keyspace<'a, 'b> {
             ));
         //keyspace.push_shortcut(&partition[0], 0);
         } else {
-            debug_assert!(partition.len() > 0);
+            debug_assert!(!partition.is_empty());
             self.push_shortcut(&partition[0], chord_index);
         }
     }
@@ -167,7 +171,7 @@ impl<'a, 'b, 'c> VisitedTracker<'a, 'b, 'c> {
             chord_index,
         };
 
-        debug_assert!(container_partition.len() >= 1);
+        debug_assert!(!container_partition.is_empty());
         debug_assert!(chord_index < container_partition[0].hotkey.0.len());
 
         let mut index = shortcut_index;
@@ -195,14 +199,14 @@ struct SharedChordPartitioner<'a, 'b, 'c> {
 impl<'a, 'b, 'c> Iterator for SharedChordPartitioner<'a, 'b, 'c> {
     type Item = &'c [Shortcut<'a, 'b>];
     fn next(&mut self) -> Option<Self::Item> {
-        if self.list.len() > 0 {
+        if !self.list.is_empty() {
             let y = self.chord_index;
             let first_hotkey = self.list[0].hotkey.0;
             if first_hotkey.len() > self.chord_index {
                 let mut close = 1;
                 for shortcut in &self.list[1..] {
                     let hotkey = shortcut.hotkey.0;
-                    if y >= hotkey.len() || &hotkey[y] != &first_hotkey[y] {
+                    if y >= hotkey.len() || hotkey[y] != first_hotkey[y] {
                         break;
                     }
                     close += 1;
@@ -22,21 +22,45 @@
 
 #![allow(dead_code)]
 
+mod backend;
 mod constants;
+mod error;
 mod keyspace;
 mod parser;
+mod region;
+mod stream;
+mod template;
 
 //use std::fs::File;
 //use std::io::{BufRead, BufReader};
+use backend::Format;
+use error::StepError;
 use keyspace::{Keyspace, KeyspaceList};
 use parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+use region::Region;
+use stream::StreamFormat;
+use template::Template;
 
 // This is per entry
 const PERMUTATION_LIMIT: usize = 1000;
 
+// How 'keyspace-list' runs its command
+#[derive(Clone, Copy)]
+enum KeyspaceProtocol {
+    // Once per keyspace with '<title> [<chord> state|run <arg>]...'
+    Sequential,
+    // The same, but all at once with the output printed in order
+    Parallel,
+    // Once with 'begin <title> [<chord> state|run <arg>]... end' repeated
+    Batch,
+}
+
 enum ListType {
-    Shortcuts,
-    Keyspaces,
+    Shortcuts(ClonableCommand),
+    ShortcutStream(ClonableCommand, StreamFormat),
+    Keyspaces(ClonableCommand, KeyspaceProtocol),
+    Native(Format),
+    Template(PathBuf),
 }
 
 fn display_help(msg: String) -> ! {
@@ -45,13 +69,16 @@ fn display_help(msg: String) -> ! {
 }
 
 use std::{
+    collections::VecDeque,
     env, fs,
+    io::{self, Read, Write},
     path::{Path, PathBuf},
-    process::{exit, Command},
+    process::{exit, Child, Command, Stdio},
+    thread,
 };
 
 fn main() {
-    let (config, output_type, command_builder) = parse_args();
+    let (config, output_type, region) = parse_args();
     let file = match fs::read_to_string(&config) {
         Ok(s) => s,
         Err(err) => {
@@ -59,29 +86,121 @@ fn main() {
             exit(1)
         }
     };
-    let metadata = validate_and_calculate_allocations(file.as_str()).or_die(1);
-    let parser_storage = parse_into_shortcut_list(metadata).or_die(1);
-    match output_type {
-        ListType::Shortcuts => {
-            let list = parser_storage.allocate_shortcut_list().or_die(1);
+    // Collect errors across all the passes so they can be reported together
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(file.as_str(), &mut errors);
+    let parser_storage = parse_into_shortcut_list(metadata, &mut errors);
+    // Empty when a command printed straight to stdout
+    let output = match output_type {
+        ListType::Shortcuts(command_builder) => {
+            let list = parser_storage
+                .allocate_shortcut_list()
+                .or_die(errors, &config, &file);
             let mut command = command_builder.instantiate();
             list.iter().for_each(|shortcut| {
                 command.arg(format!("{}", shortcut.hotkey));
-                command.arg(format!("{}", shortcut.action.join("")));
+                command.arg(shortcut.action.join(""));
             });
-            command_builder.run_and_exit_on_error(command);
+            command_builder.run_and_exit_on_error(command)
+        }
+        ListType::ShortcutStream(command_builder, stream_format) => {
+            let shortcuts = parser_storage
+                .allocate_shortcut_list()
+                .or_die(errors, &config, &file);
+            let keyspaces = parser_storage
+                .allocate_keyspace_list()
+                .or_die(Vec::new(), &config, &file);
+            command_builder.run_with_stdin_and_exit_on_error(|stdin| {
+                stream_format.write_shortcuts(stdin, &shortcuts, &keyspaces)
+            })
         }
-        ListType::Keyspaces => {
-            let list = parser_storage.allocate_keyspace_list().or_die(1);
-            list.iter().for_each(|keyspace| {
+        ListType::Keyspaces(command_builder, protocol) => {
+            let list = parser_storage
+                .allocate_keyspace_list()
+                .or_die(errors, &config, &file);
+            let commands = list.iter().map(|keyspace| {
                 let (title, chord_action_bi_list) = format_keyspace(keyspace);
                 let mut command = command_builder.instantiate();
                 command.arg(title);
                 command.args(chord_action_bi_list);
-                command_builder.run_and_exit_on_error(command);
+                command
             });
+            match protocol {
+                KeyspaceProtocol::Sequential => commands
+                    .flat_map(|command| command_builder.run_and_exit_on_error(command))
+                    .collect(),
+                KeyspaceProtocol::Parallel => command_builder.run_all_and_exit_on_error(commands),
+                KeyspaceProtocol::Batch => {
+                    let mut command = command_builder.instantiate();
+                    command.args(format_keyspace_batch(&list));
+                    command_builder.run_and_exit_on_error(command)
+                }
+            }
+        }
+        ListType::Native(format) => {
+            // The '--keymode's can only be checked against the keyspaces now
+            let format = match format {
+                Format::Fluxbox(keymodes) if errors.is_empty() => {
+                    match parser_storage.allocate_keyspace_list() {
+                        Ok(list) => Format::Fluxbox(
+                            backend::fluxbox::keymode_identifiers(&list, &keymodes)
+                                .unwrap_or_else(|msg| display_help(msg)),
+                        ),
+                        Err(_) => Format::Fluxbox(keymodes), // Reported by 'render()'
+                    }
+                }
+                format => format,
+            };
+            let output = format
+                .render(&parser_storage, &file)
+                .or_die(errors, &config, &file);
+            output.into_bytes()
         }
+        ListType::Template(path) => {
+            let template_source = match fs::read_to_string(&path) {
+                Ok(s) => s,
+                Err(err) => {
+                    eprintln!("Error reading template: {:?}\n{}", path.display(), err);
+                    exit(1)
+                }
+            };
+            let template = match Template::parse(&template_source) {
+                Ok(t) => t,
+                Err(err) => {
+                    eprintln!("{}", err.render(&path, &template_source));
+                    exit(1)
+                }
+            };
+            // Parsed before the config is checked, so its mistakes are reported first
+            let shortcuts = parser_storage
+                .allocate_shortcut_list()
+                .or_die(errors, &config, &file);
+            let keyspaces = parser_storage
+                .allocate_keyspace_list()
+                .or_die(Vec::new(), &config, &file);
+            match template.render(&shortcuts, &keyspaces) {
+                Ok(output) => output.into_bytes(),
+                Err(err) => {
+                    eprintln!("{}", err.render(&path, &template_source));
+                    exit(1)
+                }
+            }
+        }
+    };
+
+    let written = match region {
+        Some(region) => match String::from_utf8(output) {
+            Ok(output) => region.write(&output),
+            Err(_) => Err("The output to write is not valid UTF-8".into()),
+        },
+        None => io::stdout()
+            .write_all(&output)
+            .map_err(|err| format!("Error writing to stdout\n{}", err)),
     };
+    if let Err(msg) = written {
+        eprintln!("{}", msg);
+        exit(1)
+    }
 
     //run: cargo build; time cargo run config.txt keyspace-list ./keyspace-list.sh api
 }
@@ -107,10 +226,26 @@ fn format_keyspace(keyspace: &Keyspace) -> (String, Vec<String>) {
     (format!("{}", keyspace.title), chord_actions)
 }
 
+// 'begin <title> [<chord> state|run <arg>]... end' for every keyspace
+fn format_keyspace_batch(list: &[Keyspace]) -> Vec<String> {
+    let mut args = Vec::new();
+    list.iter().for_each(|keyspace| {
+        let (title, chord_action_bi_list) = format_keyspace(keyspace);
+        args.push("begin".into());
+        args.push(title);
+        args.extend(chord_action_bi_list);
+        args.push("end".into());
+    });
+    args
+}
+
 
 struct ClonableCommand {
     process: String,
     args: Vec<String>,
+    // Return what the command prints, e.g. for '--output', instead of
+    // passing it through to stdout
+    capture_stdout: bool,
 }
 
 impl ClonableCommand {
@@ -120,11 +255,14 @@ impl ClonableCommand {
         command
     }
 
-    fn run_and_exit_on_error(&self, mut command: Command) -> i32 {
-        match command.status() {
-            Ok(status) if status.success() => 0,
-            Ok(status) => {
-                let code = status.code().unwrap_or(0);
+    fn run_and_exit_on_error(&self, mut command: Command) -> Vec<u8> {
+        if self.capture_stdout {
+            command.stdout(Stdio::piped());
+        }
+        match command.spawn().and_then(Child::wait_with_output) {
+            Ok(output) if output.status.success() => output.stdout,
+            Ok(output) => {
+                let code = output.status.code().unwrap_or(0);
                 eprintln!("{:?}: exited with code {}", self.process, code);
                 exit(code)
             }
@@ -134,11 +272,133 @@ impl ClonableCommand {
             }
         }
     }
+
+    // Runs up to 'available_parallelism()' commands at once, but prints their
+    // stdout in order. On the first that fails, after printing what it output,
+    // the others are killed and waited on before exiting
+    fn run_all_and_exit_on_error<I>(&self, mut commands: I) -> Vec<u8>
+    where
+        I: Iterator<Item = Command>,
+    {
+        let limit = thread::available_parallelism().map_or(1, usize::from);
+        let mut running = VecDeque::with_capacity(limit);
+        let mut stdout = io::stdout();
+        let mut captured = Vec::new();
+        loop {
+            while running.len() < limit {
+                match commands.next().map(|mut c| c.stdout(Stdio::piped()).spawn()) {
+                    Some(Ok(child)) => running.push_back(child),
+                    Some(Err(err)) => {
+                        kill_all_and_exit(running, format!("{:?}: {}", self.process, err), 1)
+                    }
+                    None => break,
+                }
+            }
+            let child = match running.pop_front() {
+                Some(child) => child,
+                None => return captured,
+            };
+            let output = match child.wait_with_output() {
+                Ok(output) => output,
+                Err(err) => kill_all_and_exit(running, format!("{:?}: {}", self.process, err), 1),
+            };
+            if self.capture_stdout {
+                captured.extend(output.stdout);
+            } else if let Err(err) = stdout.write_all(&output.stdout).and_then(|_| stdout.flush()) {
+                kill_all_and_exit(running, format!("Error writing to stdout\n{}", err), 1)
+            }
+            if !output.status.success() {
+                let code = output.status.code().unwrap_or(0);
+                let msg = format!("{:?}: exited with code {}", self.process, code);
+                kill_all_and_exit(running, msg, code)
+            }
+        }
+    }
+
+    // The command is spawned once and 'write' streams to its stdin
+    fn run_with_stdin_and_exit_on_error<F>(&self, write: F) -> Vec<u8>
+    where
+        F: FnOnce(&mut dyn Write) -> io::Result<()>,
+    {
+        let mut command = self.instantiate();
+        command.stdin(Stdio::piped());
+        if self.capture_stdout {
+            command.stdout(Stdio::piped());
+        }
+        let mut child = match command.spawn() {
+            Ok(child) => child,
+            Err(err) => {
+                eprintln!("{:?}: {}", self.process, err);
+                exit(1)
+            }
+        };
+        // Read on another thread so a full stdout pipe cannot block the writes
+        let reader = child.stdout.take().map(|mut stdout| {
+            thread::spawn(move || {
+                let mut captured = Vec::new();
+                stdout.read_to_end(&mut captured).map(|_| captured)
+            })
+        });
+        let mut stdin = child.stdin.take().expect("DEV: stdin is piped");
+        let written = write(&mut stdin);
+        drop(stdin); // So that the command sees the end of input
+        let status = child.wait();
+        let captured = match reader.map(|reader| reader.join().expect("DEV: Reading cannot panic")) {
+            Some(Ok(captured)) => captured,
+            Some(Err(err)) => {
+                eprintln!("{:?}: Error reading stdout\n{}", self.process, err);
+                exit(1)
+            }
+            None => Vec::new(),
+        };
+        match (written, status) {
+            (_, Ok(status)) if !status.success() => {
+                let code = status.code().unwrap_or(1);
+                eprintln!("{:?}: exited with code {}", self.process, code);
+                exit(code)
+            }
+            // The command chose to stop reading and still succeeded
+            (Err(err), Ok(_)) if err.kind() == io::ErrorKind::BrokenPipe => captured,
+            (Err(err), _) => {
+                eprintln!("{:?}: Error writing to stdin\n{}", self.process, err);
+                exit(1)
+            }
+            (Ok(()), Ok(_)) => captured,
+            (Ok(()), Err(err)) => {
+                eprintln!("{:?}: {}", self.process, err);
+                exit(1)
+            }
+        }
+    }
 }
 
 
-fn parse_args() -> (PathBuf, ListType, ClonableCommand) {
-    let mut args_iter = env::args();
+fn kill_all_and_exit(children: VecDeque<Child>, msg: String, code: i32) -> ! {
+    eprintln!("{}", msg);
+    for mut child in children {
+        let _ = child.kill();
+        let _ = child.wait();
+    }
+    exit(code)
+}
+
+// shortcuts <config> --format <format>
+// shortcuts <config> --format fluxbox [--keymode <keyspace title or identifier> ...]
+// shortcuts <config> --template <file>
+// shortcuts <config> shortcut-list <command> [<arg> ...]
+// shortcuts <config> --stdin nul|json shortcut-list <command> [<arg> ...]
+// shortcuts <config> [--batch | --parallel] keyspace-list <command> [<arg> ...]
+//
+// Any of these can take '--output <file> --region <name>' before the list
+// type, to write into a marked region of <file> instead of stdout
+fn parse_args() -> (PathBuf, ListType, Option<Region>) {
+    parse_args_from(env::args())
+}
+
+fn parse_args_from<I>(mut args_iter: I) -> (PathBuf, ListType, Option<Region>)
+where
+    I: Iterator<Item = String>,
+{
     args_iter.next(); // skip $0
     let config = match args_iter.next() {
         Some(s) => PathBuf::from(s),
@@ -146,21 +406,115 @@ fn parse_args() -> (PathBuf, ListType, ClonableCommand) {
     };
     error_if_file_missing(config.as_path());
 
-    let output_type = match args_iter.next() {
-        Some(s) if s.as_str() == "shortcut-list" => ListType::Shortcuts,
-        Some(s) if s.as_str() == "keyspace-list" => ListType::Keyspaces,
-        Some(s) => display_help(format!("{:?} is an invalid choice.", s)),
-        _ => display_help("No list format specified".into()),
+    let mut format = None;
+    let mut keymodes = Vec::new();
+    let mut template = None;
+    let mut stream_format = None;
+    let mut protocol = None;
+    let mut output = None;
+    let mut region = None;
+    let mut list_type = None;
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--format" => {
+                let name = match args_iter.next() {
+                    Some(s) => s,
+                    _ => display_help("No format specified for '--format'".into()),
+                };
+                match Format::from_name(name.as_str()) {
+                    Some(f) => format = Some(f),
+                    _ => display_help(format!("{:?} is not a supported format.", name)),
+                }
+            }
+            "--keymode" => match args_iter.next() {
+                Some(s) => keymodes.push(s),
+                _ => display_help("No keyspace specified for '--keymode'".into()),
+            },
+            "--template" => match args_iter.next() {
+                Some(s) => template = Some(PathBuf::from(s)),
+                _ => display_help("No template file specified for '--template'".into()),
+            },
+            "--stdin" => match args_iter.next().as_deref().map(StreamFormat::from_name) {
+                Some(Some(f)) => stream_format = Some(f),
+                Some(None) => display_help("'--stdin' is either 'nul' or 'json'".into()),
+                None => display_help("No record format specified for '--stdin'".into()),
+            },
+            "--batch" | "--parallel" if protocol.is_some() => {
+                display_help("Choose one of '--batch' or '--parallel'".into())
+            }
+            "--batch" => protocol = Some(KeyspaceProtocol::Batch),
+            "--parallel" => protocol = Some(KeyspaceProtocol::Parallel),
+            "shortcut-list" if protocol.is_some() => {
+                display_help("'--batch' and '--parallel' are only for 'keyspace-list'".into())
+            }
+            "--output" => match args_iter.next() {
+                Some(s) => output = Some(PathBuf::from(s)),
+                _ => display_help("No file specified for '--output'".into()),
+            },
+            "--region" => match args_iter.next() {
+                Some(s) => region = Some(s),
+                _ => display_help("No region name specified for '--region'".into()),
+            },
+            "shortcut-list" => {
+                let command = parse_command(args_iter, output.is_some());
+                list_type = Some(match stream_format {
+                    Some(f) => ListType::ShortcutStream(command, f),
+                    None => ListType::Shortcuts(command),
+                });
+                break;
+            }
+            "keyspace-list" if stream_format.is_some() => {
+                display_help("'--stdin' is only for 'shortcut-list'".into())
+            }
+            "keyspace-list" => {
+                let command = parse_command(args_iter, output.is_some());
+                let protocol = protocol.unwrap_or(KeyspaceProtocol::Sequential);
+                list_type = Some(ListType::Keyspaces(command, protocol));
+                break;
+            }
+            _ => display_help(format!("{:?} is an invalid choice.", arg)),
+        }
+    }
+
+    let list_type = match (list_type, format, template) {
+        (Some(list_type), _, _) => list_type,
+        _ if stream_format.is_some() => {
+            display_help("'--stdin' is only for 'shortcut-list'".into())
+        }
+        _ if protocol.is_some() => {
+            display_help("'--batch' and '--parallel' are only for 'keyspace-list'".into())
+        }
+        (_, Some(_), Some(_)) => display_help("Choose one of '--format' or '--template'".into()),
+        (_, Some(Format::Fluxbox(_)), None) => ListType::Native(Format::Fluxbox(keymodes)),
+        _ if !keymodes.is_empty() => {
+            display_help("'--keymode' is only for '--format fluxbox'".into())
+        }
+        (_, Some(f), None) => ListType::Native(f),
+        (_, None, Some(path)) => ListType::Template(path),
+        (_, None, None) => display_help("No list format specified".into()),
     };
 
+    match (output, region) {
+        (Some(path), Some(name)) => (config, list_type, Some(Region { path, name })),
+        (None, None) => (config, list_type, None),
+        (Some(_), None) => display_help("'--output' needs a '--region' to replace".into()),
+        (None, Some(_)) => display_help("'--region' is only for '--output'".into()),
+    }
+}
+
+fn parse_command<I>(mut args_iter: I, capture_stdout: bool) -> ClonableCommand
+where
+    I: Iterator<Item = String>,
+{
     let command_string = match args_iter.next() {
         Some(s) => s,
         _ => display_help("No command run".into()),
     };
-    (config, output_type, ClonableCommand {
+    ClonableCommand {
         process: command_string,
         args: args_iter.collect::<Vec<String>>(),
-    })
+        capture_stdout,
+    }
 }
 
 fn error_if_file_missing(path: &Path) {
@@ -177,18 +531,27 @@ fn error_if_file_missing(path: &Path) {
     }
 }
 
-trait PrintError<T> {
-    fn or_die(self, exit_code: i32) -> T;
+// 'errors' are those from previous passes. Exits if there are any at all
+trait PrintError<'a, T> {
+    fn or_die(self, errors: Vec<StepError<'a>>, path: &Path, source: &str) -> T;
 }
-impl<T, E: std::fmt::Display> PrintError<T> for Result<T, E> {
-    fn or_die(self, exit_code: i32) -> T {
+impl<'a, T> PrintError<'a, T> for Result<T, Vec<StepError<'a>>> {
+    fn or_die(self, mut errors: Vec<StepError<'a>>, path: &Path, source: &str) -> T {
         match self {
-            Ok(x) => x,
-            Err(err) => {
-                eprintln!("{}", err);
-                exit(exit_code)
-            }
+            Ok(x) if errors.is_empty() => return x,
+            Ok(_) => {}
+            Err(mut later_errors) => errors.append(&mut later_errors),
+        }
+        // Report in the order they appear in the config file
+        errors.sort_by_key(|err| err.span.as_ptr() as usize);
+        for err in &errors {
+            eprintln!("{}", err.render(path, source));
         }
+        match errors.len() {
+            1 => eprintln!("error: aborting due to previous error"),
+            n => eprintln!("error: aborting due to {} previous errors", n),
+        }
+        exit(1)
     }
 }
 
@@ -230,3 +593,39 @@ fn asdf() {
 |super h| dmenu
 "#;
 }
+
+#[test]
+fn keyspace_list_protocols() {
+    let args = |list: &[&str]| {
+        let mut args = vec!["shortcuts".to_string(), "config.txt".to_string()];
+        args.extend(list.iter().map(|s| s.to_string()));
+        args.into_iter()
+    };
+    let protocol = |list: &[&str]| match parse_args_from(args(list)) {
+        (_, ListType::Keyspaces(command, protocol), None) => {
+            assert_eq!(command.process, "./render.sh");
+            assert_eq!(command.args, ["api"]);
+            protocol
+        }
+        _ => panic!("Expected a keyspace-list"),
+    };
+    let command = ["keyspace-list", "./render.sh", "api"];
+    assert!(matches!(protocol(&command), KeyspaceProtocol::Sequential));
+    let batch = ["--batch", "keyspace-list", "./render.sh", "api"];
+    assert!(matches!(protocol(&batch), KeyspaceProtocol::Batch));
+    let parallel = ["--parallel", "keyspace-list", "./render.sh", "api"];
+    assert!(matches!(protocol(&parallel), KeyspaceProtocol::Parallel));
+
+    let source = "|super Space; super w| nmtui\n|super d|\n";
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    let list = generator.allocate_keyspace_list().unwrap();
+    assert_eq!(
+        format_keyspace_batch(&list),
+        [
+            "begin", "", "Super + Space", "state", "Super + Space", "Super + d", "run", "", "end",
+            "begin", "Super + Space", "Super + w", "run", "nmtui", "end",
+        ]
+    );
+}
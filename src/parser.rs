@@ -1,27 +1,40 @@
 use super::PERMUTATION_LIMIT;
 use crate::constants::*;
+use crate::error::StepError;
 
 use std::borrow::Cow;
 use std::cmp::max;
 use std::mem::replace;
 use std::ops::Range;
 
-type StepError = String;
-type PassOutput<'a> = Result<(), StepError>;
+type PassOutput<'a> = Result<(), StepError<'a>>;
 
 pub struct PermutationsGenerator<'a> {
     //entries: Vec<
     head_calculator_memory: Vec<usize>,
     body_calculator_memory: Vec<usize>,
 
-    partitioning: Vec<(usize, usize)>,
+    partitioning: Vec<Partition<'a>>,
     chords_memory: Vec<Chord>,
     action_memory: Vec<Cow<'a, str>>, // Dealing with escaping with owned data
+    choices_memory: Vec<usize>,
+    comments: Vec<&'a str>,
 }
 
-pub fn parse_into_shortcut_list(
-    first_pass: EntryBlobMetadata,
-) -> Result<PermutationsGenerator, StepError> {
+// How much of each '*_memory' makes up a single 'Shortcut'
+struct Partition<'a> {
+    chord_count: usize,
+    action_width: usize,
+    choice_count: usize, // The number of sets in 'head'
+    head: &'a str,       // The head of the entry this was expanded from
+}
+
+// Entries that fail to parse are reported to 'errors' and left out so that
+// the rest of the config can still be checked
+pub fn parse_into_shortcut_list<'a>(
+    first_pass: EntryBlobMetadata<'a>,
+    errors: &mut Vec<StepError<'a>>,
+) -> PermutationsGenerator<'a> {
     // This is basically a lexer
     // Validate the format and calculates the sizes of allocations
     // We still do not pre-calculate the necessary number of chord allocations
@@ -37,6 +50,7 @@ pub fn parse_into_shortcut_list(
     let mut partitioning = Vec::with_capacity(head_variant_total_count);
     let mut chords_memory = Vec::new(); // TODO: calculate this capacity
     let mut body_memory = Vec::with_capacity(first_pass.total_body_space);
+    let mut choices_memory = Vec::new();
 
     for UnparsedEntry {
         row: _row,
@@ -51,44 +65,79 @@ pub fn parse_into_shortcut_list(
         let mut head_calc = Calculator::new(head, head_set_count, &mut hc_mem);
         let mut body_calc = Calculator::new(body, body_set_count, &mut bc_mem);
 
+        // For rolling back this entry if any of its variants are invalid
+        let rollback = (
+            partitioning.len(),
+            chords_memory.len(),
+            body_memory.len(),
+            choices_memory.len(),
+        );
         for i in 0..permutation_count {
             let chord_count =
-                push_head_variant(&mut chords_memory, head, head_calc.permute(i)).unwrap();
+                match push_head_variant(&mut chords_memory, head, head_calc.permute(i)) {
+                    Ok(count) => count,
+                    Err(err) => {
+                        errors.push(err);
+                        partitioning.truncate(rollback.0);
+                        chords_memory.truncate(rollback.1);
+                        body_memory.truncate(rollback.2);
+                        choices_memory.truncate(rollback.3);
+                        break;
+                    }
+                };
             //let action_mem_width = body_set_count * 2 + 1;
             let action_mem_width =
                 push_body_variant(&mut body_memory, body.trim(), body_calc.permute(i));
-            partitioning.push((chord_count, action_mem_width));
+            choices_memory.extend_from_slice(head_calc.permute(i));
+            partitioning.push(Partition {
+                chord_count,
+                action_width: action_mem_width,
+                choice_count: head_set_count,
+                head,
+            });
         }
     }
-    Ok(PermutationsGenerator {
+    PermutationsGenerator {
         head_calculator_memory: hc_mem,
         body_calculator_memory: bc_mem,
 
         partitioning,
         chords_memory,
         action_memory: body_memory,
-    })
+        choices_memory,
+        comments: first_pass.comments,
+    }
 }
 
 impl<'a> PermutationsGenerator<'a> {
+    // The comment lines between entries, in the order of the config
+    pub fn comments(&self) -> &[&'a str] {
+        &self.comments
+    }
+
     // Easier for debugging
     fn allocate_unsorted_unchecked_shortcut_list<'b>(
         &'b self,
-    ) -> Result<Vec<Shortcut<'a, 'b>>, StepError> {
+    ) -> Result<Vec<Shortcut<'a, 'b>>, StepError<'a>> {
         let len = self.partitioning.len();
 
         let mut shortcut_list = Vec::with_capacity(len);
         let mut chords_buffer = &self.chords_memory[..];
         let mut action_buffer = &self.action_memory[..];
-
-        for (chords_count, action_width) in &self.partitioning {
-            let hotkey = &chords_buffer[0..*chords_count];
-            let action = &action_buffer[0..*action_width];
-            chords_buffer = &chords_buffer[*chords_count..];
-            action_buffer = &action_buffer[*action_width..];
+        let mut choices_buffer = &self.choices_memory[..];
+
+        for partition in &self.partitioning {
+            let (hotkey, rest) = chords_buffer.split_at(partition.chord_count);
+            chords_buffer = rest;
+            let (action, rest) = action_buffer.split_at(partition.action_width);
+            action_buffer = rest;
+            let (choices, rest) = choices_buffer.split_at(partition.choice_count);
+            choices_buffer = rest;
             shortcut_list.push(Shortcut {
                 hotkey: Hotkey(hotkey),
                 action,
+                head: partition.head,
+                choices,
             });
         }
         debug_assert_eq!(
@@ -106,23 +155,101 @@ impl<'a> PermutationsGenerator<'a> {
     }
 
     // Sorted and validated 'shorcut_list'
-    pub fn allocate_shortcut_list<'b>(&'b self) -> Result<Vec<Shortcut<'a, 'b>>, StepError> {
-        // TODO: unit test for duplicates
-        let mut shortcut_list = self.allocate_unsorted_unchecked_shortcut_list()?;
+    // Reports every conflict rather than just the first
+    pub fn allocate_shortcut_list<'b>(
+        &'b self,
+    ) -> Result<Vec<Shortcut<'a, 'b>>, Vec<StepError<'a>>> {
+        let mut shortcut_list = self
+            .allocate_unsorted_unchecked_shortcut_list()
+            .map_err(|err| vec![err])?;
         shortcut_list.sort_unstable();
-        for i in 0..shortcut_list.len() - 1 {
-            let chord_list1 = &shortcut_list[i].hotkey.0;
-            let chord_list2 = &shortcut_list[i + 1].hotkey.0;
-            let len = std::cmp::min(chord_list1.len(), chord_list2.len());
-            if chord_list1[0..len] == chord_list2[0..len] {
-                return Err(format!(
-                    "Duplicate keys {} and {}",
-                    shortcut_list[i].hotkey,
-                    shortcut_list[i + 1].hotkey,
-                ));
-            }
+
+        // When sorted, every hotkey that starts with 'shortcut.hotkey'
+        // (including itself) immediately follows 'shortcut'
+        let mut errors = Vec::new();
+        for (i, shortcut) in shortcut_list.iter().enumerate() {
+            let prefix = shortcut.hotkey.0;
+            shortcut_list[i + 1..]
+                .iter()
+                .take_while(|other| other.hotkey.0.starts_with(prefix))
+                .for_each(|other| errors.push(conflict_error(shortcut, other)));
+        }
+        if errors.is_empty() {
+            Ok(shortcut_list)
+        } else {
+            Err(errors)
         }
-        Ok(shortcut_list)
+    }
+}
+
+impl<'a, 'b> Shortcut<'a, 'b> {
+    // Pairs of the '{{..}}' set in the head and which member was chosen
+    pub fn set_choices(&self) -> Vec<(&'a str, &'a str)> {
+        DelimSplit::new(self.head, 1, split_brackets)
+            .map(|(_, set, _)| set)
+            .filter(|set| !set.is_empty())
+            .zip(self.choices)
+            .map(|(set, choice)| {
+                let members = &set["{{".len()..set.len() - "}}".len()];
+                let member = members.split(',').nth(*choice).unwrap_or("");
+                (set, member.trim())
+            })
+            .collect()
+    }
+
+    fn describe_choices(&self) -> String {
+        let choices = self.set_choices();
+        if choices.is_empty() {
+            String::new()
+        } else {
+            let list = choices
+                .iter()
+                .map(|(set, member)| format!("{:?} from {}", member, set))
+                .collect::<Vec<_>>();
+            format!(" (with {})", list.join(", "))
+        }
+    }
+}
+
+// 'shorter.hotkey' is a prefix of, or the same as, 'longer.hotkey'
+fn conflict_error<'a>(shorter: &Shortcut<'a, '_>, longer: &Shortcut<'a, '_>) -> StepError<'a> {
+    if shorter.hotkey == longer.hotkey {
+        // Point at whichever is defined later in the file
+        let (first, second) = if shorter.head.as_ptr() <= longer.head.as_ptr() {
+            (shorter, longer)
+        } else {
+            (longer, shorter)
+        };
+        StepError::new(
+            format!(
+                "Duplicate hotkey {}{}",
+                second.hotkey,
+                second.describe_choices()
+            ),
+            second.head,
+        )
+        .with_note(
+            format!("First defined here{}", first.describe_choices()),
+            first.head,
+        )
+    } else {
+        StepError::new(
+            format!(
+                "Hotkey {} can never be pressed as it is shadowed by {}{}",
+                longer.hotkey,
+                shorter.hotkey,
+                longer.describe_choices()
+            ),
+            longer.head,
+        )
+        .with_note(
+            format!(
+                "{} is defined here{}",
+                shorter.hotkey,
+                shorter.describe_choices()
+            ),
+            shorter.head,
+        )
     }
 }
 
@@ -160,6 +287,7 @@ impl<'a> UnparsedEntry<'a> {
 #[derive(Debug)]
 pub struct EntryBlobMetadata<'a> {
     entries: Vec<UnparsedEntry<'a>>,
+    comments: Vec<&'a str>, // See 'comments_between_entries()'
     max_head_set_count: usize,
     max_body_set_count: usize,
     //total_head_space: usize,
@@ -169,6 +297,7 @@ impl<'a> EntryBlobMetadata<'a> {
     fn new(after_first_pipe: &'a str) -> Self {
         Self {
             entries: Vec::with_capacity(after_first_pipe.split("\n|").count()),
+            comments: Vec::new(),
             max_head_set_count: 0,
             max_body_set_count: 0,
             //total_head_space: 0,
@@ -180,15 +309,15 @@ impl<'a> EntryBlobMetadata<'a> {
         &mut self,
         body_permutation_count: usize,
         entry: UnparsedEntry<'a>,
-    ) -> Result<(), StepError> {
+    ) -> PassOutput<'a> {
         if body_permutation_count > entry.permutation_count {
-            //println!("{:?}\n{:?}", entry.head, entry.body);
-            //println!("head count {:?}", entry.permutation_count);
-            //println!("body count {:?}", entry.permutation_count);
-            Err(
-                "This body for (TODO) needs more options than there are hotkey permutations for"
-                    .into(),
-            )
+            Err(StepError::new(
+                format!(
+                    "This body has {} permutations but its hotkey only has {}. The body cannot have more options than there are hotkey permutations",
+                    body_permutation_count, entry.permutation_count,
+                ),
+                entry.body.trim(),
+            ))
         } else {
             self.max_head_set_count = max(self.max_head_set_count, entry.head_set_count);
             self.max_body_set_count = max(self.max_body_set_count, entry.body_set_count);
@@ -212,6 +341,7 @@ struct FiniteStateMachine<'a> {
     state: State,
 
     key_start_index: usize,
+    set_start_index: usize,
     head_set_size: usize,
     body_set_size: usize,
     entry_body_permutation_count: usize,
@@ -220,47 +350,92 @@ struct FiniteStateMachine<'a> {
 
     entry: UnparsedEntry<'a>,
     metadata: EntryBlobMetadata<'a>,
+    errors: Vec<StepError<'a>>,
 }
 
-pub fn validate_and_calculate_allocations(source: &str) -> Result<EntryBlobMetadata, String> {
-    let (text, start_row) = FiniteStateMachine::step_init_until_first(source)?;
+// On an error, the entry is reported to 'errors' and skipped, resuming at
+// the start of the next entry (the next '\n|')
+pub fn validate_and_calculate_allocations<'a>(
+    source: &'a str,
+    errors: &mut Vec<StepError<'a>>,
+) -> EntryBlobMetadata<'a> {
+    let (text, start_row) = FiniteStateMachine::step_init_until_first(source, errors);
     let mut fsm = FiniteStateMachine {
         original: text,
         walker: CharsWithIndex::new(text, start_row),
         state: State::Head,
 
         key_start_index: 0,
+        set_start_index: 0,
         head_set_size: 0,
         body_set_size: 0,
-        entry_body_permutation_count: 0,
+        entry_body_permutation_count: 1,
         hotkeys_count: 0,
         actions_count: 0,
 
         entry: UnparsedEntry::new(text, start_row),
         metadata: EntryBlobMetadata::new(text),
+        errors: Vec::new(),
     };
 
     while let Some(ch) = fsm.walker.next() {
-        match fsm.state {
-            State::Head => fsm.step_head(ch)?,
-            State::HeadBrackets => fsm.step_head_brackets(ch)?,
-            State::Body => fsm.step_body(ch)?, // This may push
-            State::BodyBrackets => fsm.step_body_brackets(ch)?,
+        let result = match fsm.state {
+            State::Head => fsm.step_head(ch),
+            State::HeadBrackets => fsm.step_head_brackets(ch),
+            State::Body => fsm.step_body(ch), // This may push
+            State::BodyBrackets => fsm.step_body_brackets(ch),
         };
+        if let Err(err) = result {
+            fsm.errors.push(err);
+            fsm.recover_at_next_entry();
+        }
     }
     if let State::HeadBrackets | State::BodyBrackets = fsm.state {
-        return Err("Brackets not closed. Expected a '}}'".into());
+        let err = fsm.unclosed_set_error();
+        fsm.errors.push(err);
+    } else if !fsm.entry.head.is_empty() {
+        let last = fsm.entry;
+        if let Err(err) = fsm
+            .metadata
+            .push_entry(fsm.entry_body_permutation_count, last)
+        {
+            fsm.errors.push(err);
+        }
     }
-    let last = fsm.entry;
-    if !last.head.is_empty() {
-        fsm.metadata
-            .push_entry(fsm.entry_body_permutation_count, last)?;
+    errors.append(&mut fsm.errors);
+    fsm.metadata.comments = comments_between_entries(source);
+    fsm.metadata
+}
+
+// A line whose first non-whitespace character is '#'
+pub fn is_comment_line(line: &str) -> bool {
+    line.trim_start().starts_with('#')
+}
+
+// The comment lines with only comment or blank lines between them and the
+// next entry (or the end of the config), e.g. for section headings. They
+// are still part of the body before them like any other line
+fn comments_between_entries(source: &str) -> Vec<&str> {
+    let mut comments = Vec::new();
+    let mut trailing = Vec::new();
+    for line in source.lines() {
+        if line.starts_with('|') {
+            comments.append(&mut trailing);
+        } else if is_comment_line(line) {
+            trailing.push(line.trim());
+        } else if !line.trim().is_empty() {
+            trailing.clear();
+        }
     }
-    Ok(fsm.metadata)
+    comments.append(&mut trailing);
+    comments
 }
 
 impl<'a> FiniteStateMachine<'a> {
-    fn step_init_until_first(source: &str) -> Result<(&str, usize), StepError> {
+    fn step_init_until_first(
+        source: &'a str,
+        errors: &mut Vec<StepError<'a>>,
+    ) -> (&'a str, usize) {
         let mut row = 0;
         let mut start = source.len();
         for line in source.lines() {
@@ -270,18 +445,41 @@ impl<'a> FiniteStateMachine<'a> {
                 start = line.as_ptr() as usize - source.as_ptr() as usize + one;
                 break;
             }
-            match line.trim_start().chars().next() {
-                Some('#') => {}
-                Some(_) => return Err("Lines can only be a comment (first non-whitespace character is '#') or whitespace before the first entry (first character in line is '|')".into()),
-                None => {}
+            if !is_comment_line(line) && !line.trim().is_empty() {
+                errors.push(StepError::new("Lines can only be a comment (first non-whitespace character is '#') or whitespace before the first entry (first character in line is '|')", line.trim()));
             }
         }
 
-        Ok((&source[start..], row))
+        (&source[start..], row)
+    }
+
+    // Discards the current entry and skips to the start of the next one
+    // Call right after the step that errored
+    fn recover_at_next_entry(&mut self) {
+        loop {
+            let prev = self.walker.prev;
+            let is_entry_start = self.current_char() == "|"
+                && prev > 0
+                && self.original[..prev].ends_with('\n');
+            if is_entry_start {
+                self.entry =
+                    UnparsedEntry::new(&self.original[self.walker.post..], self.walker.row);
+                self.entry_body_permutation_count = 1;
+                self.state = State::Body; // So that 'change_state()' does no set work
+                self.change_state(State::Head).ok(); // Never errors to 'State::Head'
+                return;
+            }
+            if self.walker.next().is_none() {
+                break;
+            }
+        }
+        // Reached the end of file, nothing to push
+        self.entry.head = &self.original[self.original.len()..];
+        self.state = State::Head;
     }
 
     #[inline]
-    fn step_head(&mut self, ch: char) -> PassOutput {
+    fn step_head(&mut self, ch: char) -> PassOutput<'a> {
         match ch {
             '|' => {
                 let base = self.original.as_ptr() as usize;
@@ -292,17 +490,18 @@ impl<'a> FiniteStateMachine<'a> {
                                                  //println!("==={:?}===\n{:?}", self.entry.head, self.entry.body);
             }
             '{' => {
+                let brace = self.current_char();
                 if let Some('{') = self.walker.next() {
                     // Want these three things on
                     self.change_state(State::HeadBrackets)?; // Call last
                 } else {
-                    return Err(
-                        "Missing a second opening curly brace. Need '{{' to start an enumeration"
-                            .into(),
-                    );
+                    return Err(StepError::new(
+                        "Missing a second opening curly brace. Need '{{' to start an enumeration",
+                        brace,
+                    ));
                 }
             }
-            ',' => return Err("Unexpected comma ','. Type 'comma' for the key, ';' for a chord separator. ',' only has meaning inside an enumeration group '{{..}}'".into()),
+            ',' => return Err(StepError::new("Unexpected comma ','. Type 'comma' for the key, ';' for a chord separator. ',' only has meaning inside an enumeration group '{{..}}'", self.current_char())),
             ';' => {
                 self.walker.eat_separator();
                 self.key_start_index = self.walker.post;
@@ -315,7 +514,7 @@ impl<'a> FiniteStateMachine<'a> {
                 let start = self.key_start_index;
                 let key = &self.original[start..self.walker.post];
                 if key.len() > KEYSTR_MAX_LEN {
-                    panic!("Invalid keycode {:?}", key);
+                    return Err(StepError::new(format!("Invalid keycode {:?}", key), key));
                 }
                 // Key validation check will happen when we parse the key
                 // so we do since we allocate at that time
@@ -325,21 +524,26 @@ impl<'a> FiniteStateMachine<'a> {
     }
 
     #[inline]
-    fn step_head_brackets(&mut self, ch: char) -> PassOutput {
+    fn step_head_brackets(&mut self, ch: char) -> PassOutput<'a> {
         match ch {
-            '|' => return Err("Unexpected bar '|'. Close the enumeration first with '}}'".into()),
+            '|' if self.original[..self.walker.prev].ends_with('\n') => {
+                // Ran into the next entry
+                return Err(self.unclosed_set_error());
+            }
+            '|' => return Err(StepError::new("Unexpected bar '|'. Close the enumeration first with '}}'", self.current_char())),
             '\\' => {
-                return Err("You cannot escape characters with backslash '\\' in the hotkey definition portion".into());
+                return Err(StepError::new("You cannot escape characters with backslash '\\' in the hotkey definition portion", self.current_char()));
             }
             ',' => self.head_set_member(),
             '}' => {
+                let brace = self.current_char();
                 if let Some('}') = self.walker.next() {
                     self.change_state(State::Head)?; // Call last
                 } else {
-                    return Err(
-                        "Missing a second closing curly brace. Need '}}' to close an enumeration"
-                            .into(),
-                    );
+                    return Err(StepError::new(
+                        "Missing a second closing curly brace. Need '}}' to close an enumeration",
+                        brace,
+                    ));
                 }
             }
             _ if SEPARATOR.contains(&ch) => {
@@ -352,7 +556,7 @@ impl<'a> FiniteStateMachine<'a> {
     }
 
     #[inline]
-    fn step_body(&mut self, ch: char) -> PassOutput {
+    fn step_body(&mut self, ch: char) -> PassOutput<'a> {
         match (ch, self.walker.peek()) {
             ('\n', Some('|')) => {
                 self.walker.next();
@@ -363,10 +567,13 @@ impl<'a> FiniteStateMachine<'a> {
 
                 let new_entry =
                     UnparsedEntry::new(&self.original[self.walker.post..], self.walker.row);
-                self.metadata.push_entry(
-                    self.entry_body_permutation_count,
+                if let Err(err) = self.metadata.push_entry(
+                    replace(&mut self.entry_body_permutation_count, 1),
                     replace(&mut self.entry, new_entry),
-                )?;
+                ) {
+                    // The next entry is already started, so no need to recover
+                    self.errors.push(err);
+                }
 
                 self.change_state(State::Head)?; // Call last
             }
@@ -377,17 +584,18 @@ impl<'a> FiniteStateMachine<'a> {
     }
 
     #[inline]
-    fn step_body_brackets(&mut self, ch: char) -> PassOutput {
+    fn step_body_brackets(&mut self, ch: char) -> PassOutput<'a> {
         match ch {
             '\\' => {
                 self.walker.next();
             }
             ',' => self.body_set_member(),
             '}' => {
+                let brace = self.current_char();
                 if let Some('}') = self.walker.next() {
                     self.change_state(State::Body)?; // Call last
                 } else {
-                    return Err("Missing a second closing curly brace. Need '}}' to close. If you want a '}' as output, escape it with backslash like '\\}'".into());
+                    return Err(StepError::new("Missing a second closing curly brace. Need '}}' to close. If you want a '}' as output, escape it with backslash like '\\}'", brace));
                 }
             }
             _ => {}
@@ -395,8 +603,24 @@ impl<'a> FiniteStateMachine<'a> {
         Ok(())
     }
 
+    fn unclosed_set_error(&self) -> StepError<'a> {
+        let start = self.set_start_index;
+        StepError::new(
+            "Brackets not closed. Expected a '}}' for this '{{'",
+            &self.original[start..start + "{{".len()],
+        )
+    }
+
+    // The char that 'self.walker' just returned
+    #[inline]
+    fn current_char(&self) -> &'a str {
+        &self.original[self.walker.prev..self.walker.post]
+    }
+
     #[inline]
     fn head_set_start(&mut self) {
+        // 'self.walker' is on the second '{'
+        self.set_start_index = self.walker.prev - '{'.len_utf8();
         self.walker.eat_separator();
         self.key_start_index = self.walker.post;
         self.head_set_size = 0;
@@ -410,13 +634,21 @@ impl<'a> FiniteStateMachine<'a> {
     }
 
     #[inline]
-    fn head_set_close(&mut self) -> Result<(), StepError> {
+    fn head_set_close(&mut self) -> PassOutput<'a> {
         self.head_set_size += 1;
         self.entry.permutation_count *= self.head_set_size;
         self.entry.head_set_count += 1;
         //println!("group_end {:?}", self.entry.permutation_count, )
         if self.entry.permutation_count > PERMUTATION_LIMIT {
-            Err("Too many permutations for <line>".into())
+            let base = self.original.as_ptr() as usize;
+            let offset = self.entry.head.as_ptr() as usize - base;
+            Err(StepError::new(
+                format!(
+                    "Too many permutations. This hotkey expands to more than {}",
+                    PERMUTATION_LIMIT
+                ),
+                &self.original[offset..self.walker.post],
+            ))
         } else {
             Ok(())
         }
@@ -424,6 +656,8 @@ impl<'a> FiniteStateMachine<'a> {
 
     #[inline]
     fn body_set_start(&mut self) {
+        // 'self.walker' is on the first '{'
+        self.set_start_index = self.walker.prev;
         self.body_set_size = 0;
     }
 
@@ -438,7 +672,7 @@ impl<'a> FiniteStateMachine<'a> {
         self.entry.body_set_count += 1;
     }
 
-    fn change_state(&mut self, target: State) -> Result<(), StepError> {
+    fn change_state(&mut self, target: State) -> PassOutput<'a> {
         // From 'self.state' to 'target'
         match (&self.state, &target) {
             (_, State::HeadBrackets) => self.head_set_start(),
@@ -487,16 +721,18 @@ where
 //    }
 //
 //}
-fn push_head_variant(
+fn push_head_variant<'a>(
     chord_memory: &mut Vec<Chord>,
-    head: &str,
+    head: &'a str,
     permutation: &[usize],
-) -> Result<usize, String> {
-    fn push_chord(
+) -> Result<usize, StepError<'a>> {
+    // 'span' is where the chord ends
+    fn push_chord<'a>(
         chords: &mut Vec<Chord>,
         key: &mut Option<Key>,
         modifiers: &mut Modifiers,
-    ) -> Result<(), StepError> {
+        span: &'a str,
+    ) -> PassOutput<'a> {
         if let Some(code) = std::mem::take(key) {
             chords.push(Chord {
                 key: code,
@@ -504,9 +740,9 @@ fn push_head_variant(
             });
             Ok(())
         } else {
-            Err("No key set".into())
+            Err(StepError::new("No key set for this chord", span))
         }
-    };
+    }
 
     let mut walker = DelimSplit::new(head, 1, head_lexer).peekable();
     let mut set_index = 0;
@@ -535,7 +771,7 @@ fn push_head_variant(
             "}}" => set_index += 1,
             ";" => {
                 chord_count += 1;
-                push_chord(chord_memory, &mut key, &mut modifiers)?;
+                push_chord(chord_memory, &mut key, &mut modifiers, field)?;
             }
 
             "shift" => modifiers |= Mod::Shift as Modifiers,
@@ -543,18 +779,26 @@ fn push_head_variant(
             "ctrl" => modifiers |= Mod::Ctrl as Modifiers,
             "alt" => modifiers |= Mod::Alt as Modifiers,
 
-            _ if key.is_some() => panic!("Key already defined"),
+            _ if key.is_some() => {
+                return Err(StepError::new(
+                    format!(
+                        "Key {:?} is a second key for this chord. Use ';' to separate chords",
+                        field
+                    ),
+                    field,
+                ));
+            }
             _ => {
                 if let Some(i) = KEYSTRS.iter().position(|x| *x == field) {
                     key = Some(KEYCODES[i].clone());
                 } else {
-                    return Err(format!("Key {:?} not found", field));
+                    return Err(StepError::new(format!("Key {:?} not found", field), field));
                 }
             }
         }
     }
     chord_count += 1;
-    push_chord(chord_memory, &mut key, &mut modifiers)?;
+    push_chord(chord_memory, &mut key, &mut modifiers, &head[head.len()..])?;
     Ok(chord_count)
 }
 
@@ -635,7 +879,7 @@ impl<'b> Calculator<'b> {
             let x = permutation_index / self.digit_values[i];
             self.permutation[i] = x % self.set_sizes[i];
         }
-        &self.permutation
+        self.permutation
     }
 }
 
@@ -881,3 +1125,96 @@ impl<'a> Iterator for DelimSplit<'a> {
         }
     }
 }
+
+#[test]
+fn recover_after_errors() {
+    let source = r#"
+|super foo| echo
+|super {{a,b}| echo
+|super {{c
+|super d| echo
+|super {{e,nope}}| echo
+|super {{f,g}}| echo
+"#;
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    // Entry with "foo" is only invalid once keys are parsed
+    assert_eq!(errors.len(), 2);
+    assert_eq!(metadata.entries.len(), 4);
+
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    assert_eq!(errors.len(), 4);
+    let list = generator.allocate_shortcut_list().unwrap();
+    let hotkeys = list
+        .iter()
+        .map(|shortcut| shortcut.hotkey.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(hotkeys, ["Super + d", "Super + f", "Super + g"]);
+}
+
+#[test]
+fn conflicts_are_all_reported() {
+    let source = r#"
+|super {{a,b}}| one
+|super a| two
+|super b; super c| three
+|super b; super c; super d| four
+"#;
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    assert!(errors.is_empty());
+
+    let errors = generator.allocate_shortcut_list().unwrap_err();
+    let messages = errors
+        .iter()
+        .map(|err| err.message.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "Duplicate hotkey Super + a",
+            "Hotkey Super + b ; Super + c can never be pressed as it is shadowed by Super + b",
+            "Hotkey Super + b ; Super + c ; Super + d can never be pressed as it is shadowed by Super + b",
+            "Hotkey Super + b ; Super + c ; Super + d can never be pressed as it is shadowed by Super + b ; Super + c",
+        ]
+    );
+    assert_eq!(errors[0].notes[0].0, r#"First defined here (with "a" from {{a,b}})"#);
+    assert_eq!(errors[0].notes[0].1, "super {{a,b}}");
+}
+
+#[test]
+fn comments_stay_in_bodies() {
+    let source = r#"# Apps
+|super a| echo a
+  # indented
+
+# Sets
+|super {{b,c}}| echo
+#{{1,2}} mid
+  end
+# note {{z}}
+"#;
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    assert!(errors.is_empty(), "{:?}", errors);
+    assert_eq!(
+        generator.comments(),
+        ["# Apps", "# indented", "# Sets", "# note {{z}}"]
+    );
+    // The comments are still part of the bodies
+    let list = generator.allocate_shortcut_list().unwrap();
+    let actions = list
+        .iter()
+        .map(|shortcut| shortcut.action.join(""))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        actions,
+        [
+            "echo a\n  # indented\n\n# Sets",
+            "echo\n#1 mid\n  end\n# note z",
+            "echo\n#2 mid\n  end\n# note z",
+        ]
+    );
+}
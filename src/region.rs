@@ -0,0 +1,139 @@
+// Writing the output into an existing file for '--output <file> --region
+// <name>', replacing only the lines between its markers:
+//
+//   # BEGIN shortcuts:<name>
+//   ...
+//   # END shortcuts:<name>
+//
+// The file is replaced through a temporary file in the same directory and a
+// rename, so a failed run leaves it as it was. The previous version is kept
+// as '<file>.bak'
+
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+pub struct Region {
+    pub path: PathBuf,
+    pub name: String,
+}
+
+impl Region {
+    pub fn write(&self, generated: &str) -> Result<(), String> {
+        // So a symlinked config (e.g. into a dotfiles repo) stays a symlink
+        let path = fs::canonicalize(&self.path)
+            .map_err(|err| format!("Cannot find {:?}\n{}", self.path.display(), err))?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| format!("Error reading {:?}\n{}", path.display(), err))?;
+        let updated = splice(&contents, &self.name, generated)
+            .map_err(|msg| format!("{} in {:?}", msg, path.display()))?;
+
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp = path.with_file_name(format!(".{}.{}.tmp", file_name, process::id()));
+        let backup = path.with_file_name(format!("{}.bak", file_name));
+        let write_temp = || -> std::io::Result<()> {
+            let mut file = fs::File::create(&temp)?;
+            file.write_all(updated.as_bytes())?;
+            file.set_permissions(fs::metadata(&path)?.permissions())?;
+            file.sync_all()
+        };
+        write_temp()
+            .and_then(|_| fs::copy(&path, &backup).map(|_| ()))
+            .and_then(|_| fs::rename(&temp, &path))
+            .map_err(|err| {
+                let _ = fs::remove_file(&temp);
+                format!(
+                    "Error writing {:?}, it was left unchanged\n{}",
+                    path.display(),
+                    err
+                )
+            })
+    }
+}
+
+fn splice(contents: &str, name: &str, generated: &str) -> Result<String, String> {
+    let begin_marker = format!("# BEGIN shortcuts:{}", name);
+    let end_marker = format!("# END shortcuts:{}", name);
+
+    // (start, end) byte offsets of each marker line, including its newline
+    let mut begin = None;
+    let mut end = None;
+    let mut offset = 0;
+    for line in contents.split_inclusive('\n') {
+        let range = (offset, offset + line.len());
+        offset += line.len();
+        match line.trim() {
+            l if l == begin_marker && begin.is_some() => {
+                return Err(format!("More than one '{}' line", begin_marker))
+            }
+            l if l == end_marker && end.is_some() => {
+                return Err(format!("More than one '{}' line", end_marker))
+            }
+            l if l == end_marker && begin.is_none() => {
+                return Err(format!("'{}' comes before '{}'", end_marker, begin_marker))
+            }
+            l if l == begin_marker => begin = Some(range),
+            l if l == end_marker => end = Some(range),
+            _ => {}
+        }
+    }
+
+    match (begin, end) {
+        (Some((_, after_begin)), Some((before_end, _))) => {
+            let mut spliced = String::with_capacity(contents.len() + generated.len());
+            spliced.push_str(&contents[..after_begin]);
+            if !contents[..after_begin].ends_with('\n') {
+                spliced.push('\n');
+            }
+            spliced.push_str(generated);
+            if !generated.is_empty() && !generated.ends_with('\n') {
+                spliced.push('\n');
+            }
+            spliced.push_str(&contents[before_end..]);
+            Ok(spliced)
+        }
+        (None, _) => Err(format!("No '{}' line", begin_marker)),
+        (Some(_), None) => Err(format!("No '{}' line", end_marker)),
+    }
+}
+
+#[test]
+fn region_splice() {
+    let contents = "set $mod Mod4\n# BEGIN shortcuts:i3\nold\n  # END shortcuts:i3\nbar {}";
+    assert_eq!(
+        splice(contents, "i3", "new\nlines"),
+        Ok("set $mod Mod4\n# BEGIN shortcuts:i3\nnew\nlines\n  # END shortcuts:i3\nbar {}".into())
+    );
+    assert_eq!(
+        splice(contents, "i3", ""),
+        Ok("set $mod Mod4\n# BEGIN shortcuts:i3\n  # END shortcuts:i3\nbar {}".into())
+    );
+    assert_eq!(
+        splice(contents, "sway", "new"),
+        Err("No '# BEGIN shortcuts:sway' line".into())
+    );
+    assert_eq!(
+        splice("# END shortcuts:i3\n# BEGIN shortcuts:i3\n", "i3", "new"),
+        Err("'# END shortcuts:i3' comes before '# BEGIN shortcuts:i3'".into())
+    );
+
+    let path = std::env::temp_dir().join(format!("shortcuts-region-{}", process::id()));
+    fs::write(&path, contents).unwrap();
+    let region = Region {
+        path: path.clone(),
+        name: "i3".into(),
+    };
+    region.write("new").unwrap();
+    let backup = path.with_file_name(format!(
+        "{}.bak",
+        path.file_name().unwrap().to_string_lossy()
+    ));
+    assert_eq!(fs::read_to_string(&backup).unwrap(), contents);
+    assert_eq!(
+        fs::read_to_string(&path).unwrap(),
+        "set $mod Mod4\n# BEGIN shortcuts:i3\nnew\n  # END shortcuts:i3\nbar {}"
+    );
+    fs::remove_file(&path).unwrap();
+    fs::remove_file(&backup).unwrap();
+}
@@ -0,0 +1,127 @@
+// The records written to the stdin of the 'shortcut-list' command when it is
+// run with '--stdin', instead of passing every shortcut as arguments
+//
+// Shortcuts are grouped by the keyspace they are in, in the order of the
+// keyspace list, and each group is marked even when it has no shortcuts:
+//
+//   begin    <keyspace title>
+//   shortcut <hotkey> <body>
+//   ...
+//   end      <keyspace title>
+//
+// The root keyspace has an empty title. With 'nul', every field is followed
+// by a NUL byte. With 'json', every record is one line:
+//
+//   {"type": "begin", "keyspace": "Super + Space"}
+//   {"type": "shortcut", "hotkey": "Super + Space ; Super + w", "action": "nmtui"}
+//   {"type": "end", "keyspace": "Super + Space"}
+
+use crate::backend::join_action;
+use crate::backend::json::json_string;
+use crate::constants::Shortcut;
+use crate::keyspace::Keyspace;
+use std::io::{self, Write};
+
+#[derive(Clone, Copy)]
+pub enum StreamFormat {
+    Nul,
+    JsonLines,
+}
+
+impl StreamFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "nul" => Some(StreamFormat::Nul),
+            "json" | "jsonl" => Some(StreamFormat::JsonLines),
+            _ => None,
+        }
+    }
+
+    pub fn write_shortcuts(
+        self,
+        writer: &mut dyn Write,
+        shortcuts: &[Shortcut],
+        keyspaces: &[Keyspace],
+    ) -> io::Result<()> {
+        for keyspace in keyspaces {
+            let title = keyspace.title.to_string();
+            self.write_record(writer, "begin", &[("keyspace", &title)])?;
+            for shortcut in shortcuts {
+                let chords = shortcut.hotkey.0;
+                if chords[..chords.len() - 1] == *keyspace.title.0 {
+                    self.write_record(
+                        writer,
+                        "shortcut",
+                        &[
+                            ("hotkey", &shortcut.hotkey.to_string()),
+                            ("action", &join_action(shortcut.action)),
+                        ],
+                    )?;
+                }
+            }
+            self.write_record(writer, "end", &[("keyspace", &title)])?;
+        }
+        writer.flush()
+    }
+
+    fn write_record(
+        self,
+        writer: &mut dyn Write,
+        kind: &str,
+        fields: &[(&str, &str)],
+    ) -> io::Result<()> {
+        match self {
+            StreamFormat::Nul => {
+                write!(writer, "{}\0", kind)?;
+                for (_, value) in fields {
+                    write!(writer, "{}\0", value)?;
+                }
+                Ok(())
+            }
+            StreamFormat::JsonLines => {
+                write!(writer, "{{\"type\": {}", json_string(kind))?;
+                for (key, value) in fields {
+                    write!(writer, ", {}: {}", json_string(key), json_string(value))?;
+                }
+                writeln!(writer, "}}")
+            }
+        }
+    }
+}
+
+#[test]
+fn stream_records() {
+    use crate::keyspace::KeyspaceList;
+    use crate::parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+    let source = "|super Space; super w| nmtui\n  -a\n|super d| dmenu_run\n";
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    let shortcuts = generator.allocate_shortcut_list().unwrap();
+    let keyspaces = generator.allocate_keyspace_list().unwrap();
+
+    let mut nul = Vec::new();
+    StreamFormat::Nul
+        .write_shortcuts(&mut nul, &shortcuts, &keyspaces)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(nul).unwrap(),
+        "begin\0\0shortcut\0Super + d\0dmenu_run\0end\0\0\
+         begin\0Super + Space\0shortcut\0Super + Space ; Super + w\0nmtui\n  -a\0end\0Super + Space\0"
+    );
+
+    let mut json = Vec::new();
+    StreamFormat::JsonLines
+        .write_shortcuts(&mut json, &shortcuts, &keyspaces)
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(json).unwrap(),
+        r#"{"type": "begin", "keyspace": ""}
+{"type": "shortcut", "hotkey": "Super + d", "action": "dmenu_run"}
+{"type": "end", "keyspace": ""}
+{"type": "begin", "keyspace": "Super + Space"}
+{"type": "shortcut", "hotkey": "Super + Space ; Super + w", "action": "nmtui\n  -a"}
+{"type": "end", "keyspace": "Super + Space"}
+"#
+    );
+}
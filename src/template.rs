@@ -0,0 +1,687 @@
+// A small template language, so a new target can be a template file instead
+// of a script that re-parses the output of 'keyspace-list'
+//
+//   {{ expr }}                          Outputs 'expr'
+//   {% for name in expr %}..{% endfor %}
+//   {% if [not] expr %}..{% else %}..{% endif %}
+//   {% raw %}..{% endraw %}             Outputs '..' as is, e.g. for a '{{'
+//
+// where 'expr' is 'name.field.field | filter | filter(args) ...'. A '{% %}'
+// that is alone on its line does not leave behind a blank line
+//
+// The variables at the start are 'keyspaces' and 'shortcuts', see 'field()'
+// for what each value has and 'apply()' for the filters
+
+use crate::backend::i3::quote;
+use crate::backend::json::json_string;
+use crate::backend::{identifier, join_action, sh_quote, shell_one_line};
+use crate::constants::{Chord, Shortcut};
+use crate::error::StepError;
+use crate::keyspace::{Keyspace, KeyspaceAction};
+
+type TemplateResult<'t, T> = Result<T, StepError<'t>>;
+
+pub struct Template<'t>(Vec<Node<'t>>);
+
+enum Node<'t> {
+    Text(&'t str),
+    Output(Expr<'t>),
+    For {
+        name: &'t str,
+        list: Expr<'t>,
+        body: Vec<Node<'t>>,
+    },
+    If {
+        negate: bool,
+        condition: Expr<'t>,
+        then: Vec<Node<'t>>,
+        otherwise: Vec<Node<'t>>,
+    },
+}
+
+struct Expr<'t> {
+    path: Vec<&'t str>,
+    filters: Vec<Filter<'t>>,
+}
+
+struct Filter<'t> {
+    name: &'t str,
+    args: Vec<(Option<&'t str>, String)>, // (key, value) for 'key="value"'
+}
+
+enum Token<'t> {
+    Text(&'t str),
+    Output(&'t str),
+    // The span of the whole tag for errors, then its inside
+    Tag(&'t str, &'t str),
+}
+
+/******************************************************************************
+ * Parsing
+ ******************************************************************************/
+impl<'t> Template<'t> {
+    pub fn parse(source: &'t str) -> TemplateResult<'t, Self> {
+        let mut tokens = tokenize(source)?.into_iter();
+        match parse_nodes(&mut tokens)? {
+            (nodes, None) => Ok(Template(nodes)),
+            (_, Some((keyword, tag))) => Err(StepError::new(
+                format!("Unexpected '{{% {} %}}' without an opening tag", keyword),
+                tag,
+            )),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> TemplateResult<'_, Vec<Token<'_>>> {
+    let mut tokens = Vec::new();
+    let mut rest_start = 0;
+    while let Some(offset) = source[rest_start..].find('{') {
+        let start = rest_start + offset;
+        let (close, is_tag) = match &source[start..] {
+            s if s.starts_with("{{") => ("}}", false),
+            s if s.starts_with("{%") => ("%}", true),
+            _ => {
+                tokens.push(Token::Text(&source[rest_start..start + 1]));
+                rest_start = start + 1;
+                continue;
+            }
+        };
+        let inside_start = start + 2;
+        let inside_close = match source[inside_start..].find(close) {
+            Some(i) => inside_start + i,
+            None => {
+                return Err(StepError::new(
+                    format!("Expected a '{}' to close this", close),
+                    &source[start..inside_start],
+                ))
+            }
+        };
+        let end = inside_close + 2;
+        let inside = &source[inside_start..inside_close];
+
+        if is_tag && inside.trim() == "raw" {
+            let (text_close, next) = tag_extent(source, rest_start, start, end);
+            tokens.push(Token::Text(&source[rest_start..text_close]));
+            let (endraw_start, endraw_end) = match find_endraw(source, next) {
+                Some(range) => range,
+                None => {
+                    return Err(StepError::new(
+                        "Expected a '{% endraw %}' to close this 'raw'",
+                        &source[start..end],
+                    ))
+                }
+            };
+            let (raw_close, after) = tag_extent(source, next, endraw_start, endraw_end);
+            tokens.push(Token::Text(&source[next..raw_close]));
+            rest_start = after;
+        } else if is_tag {
+            let (text_close, next) = tag_extent(source, rest_start, start, end);
+            tokens.push(Token::Text(&source[rest_start..text_close]));
+            tokens.push(Token::Tag(&source[start..end], inside));
+            rest_start = next;
+        } else {
+            tokens.push(Token::Text(&source[rest_start..start]));
+            tokens.push(Token::Output(inside));
+            rest_start = end;
+        }
+    }
+    tokens.push(Token::Text(&source[rest_start..]));
+    Ok(tokens)
+}
+
+// Where the text before the tag at 'start..end' closes and the next token
+// starts. A tag alone on its line takes the whole line with it
+fn tag_extent(source: &str, rest_start: usize, start: usize, end: usize) -> (usize, usize) {
+    let line_start = source[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_close = source[end..].find('\n').map(|i| end + i);
+    let is_alone = source[line_start..start].trim().is_empty()
+        && line_close.map_or(source[end..].trim().is_empty(), |close| {
+            source[end..close].trim().is_empty()
+        });
+    match line_close {
+        Some(close) if is_alone => (line_start.max(rest_start), close + 1),
+        None if is_alone => (line_start.max(rest_start), source.len()),
+        _ => (start, end),
+    }
+}
+
+// The first '{% endraw %}' from 'from', as the range of the tag
+fn find_endraw(source: &str, from: usize) -> Option<(usize, usize)> {
+    source[from..].match_indices("{%").find_map(|(i, _)| {
+        let start = from + i;
+        let close = source[start + 2..].find("%}")? + start + 2;
+        let is_endraw = source[start + 2..close].trim() == "endraw";
+        is_endraw.then_some((start, close + 2))
+    })
+}
+
+// Returns at the end of input or at an 'else', 'endfor' or 'endif', which is
+// returned with the span of its tag
+#[allow(clippy::type_complexity)]
+fn parse_nodes<'t>(
+    tokens: &mut std::vec::IntoIter<Token<'t>>,
+) -> TemplateResult<'t, (Vec<Node<'t>>, Option<(&'t str, &'t str)>)> {
+    let mut nodes = Vec::new();
+    while let Some(token) = tokens.next() {
+        match token {
+            Token::Text("") => {}
+            Token::Text(text) => nodes.push(Node::Text(text)),
+            Token::Output(inside) => nodes.push(Node::Output(parse_expr(inside)?)),
+            Token::Tag(tag, inside) => {
+                let trimmed = inside.trim();
+                let keyword = trimmed.split_whitespace().next().unwrap_or("");
+                let rest = trimmed[keyword.len()..].trim_start();
+                match keyword {
+                    "for" => {
+                        let name = rest.split_whitespace().next().unwrap_or("");
+                        let after_name = rest[name.len()..].trim_start();
+                        let list = match strip_word(after_name, "in") {
+                            Some(list) if is_identifier(name) => list,
+                            _ => {
+                                return Err(StepError::new(
+                                    "Expected '{% for <name> in <expr> %}'",
+                                    tag,
+                                ))
+                            }
+                        };
+                        let body = match parse_nodes(tokens)? {
+                            (body, Some(("endfor", _))) => body,
+                            (_, other) => return Err(unclosed_error("for", "endfor", tag, other)),
+                        };
+                        nodes.push(Node::For {
+                            name,
+                            list: parse_expr(list)?,
+                            body,
+                        });
+                    }
+                    "if" => {
+                        let (negate, condition) = match strip_word(rest, "not") {
+                            Some(condition) => (true, condition),
+                            None => (false, rest),
+                        };
+                        let (then, otherwise) = match parse_nodes(tokens)? {
+                            (then, Some(("endif", _))) => (then, Vec::new()),
+                            (then, Some(("else", else_tag))) => match parse_nodes(tokens)? {
+                                (otherwise, Some(("endif", _))) => (then, otherwise),
+                                (_, other) => {
+                                    return Err(unclosed_error("else", "endif", else_tag, other))
+                                }
+                            },
+                            (_, other) => return Err(unclosed_error("if", "endif", tag, other)),
+                        };
+                        nodes.push(Node::If {
+                            negate,
+                            condition: parse_expr(condition)?,
+                            then,
+                            otherwise,
+                        });
+                    }
+                    "else" | "endfor" | "endif" if rest.is_empty() => {
+                        return Ok((nodes, Some((keyword, tag))))
+                    }
+                    _ => {
+                        return Err(StepError::new(
+                            format!(
+                            "Unknown tag {:?}. Expected 'for', 'if', 'else', 'endfor' or 'endif'",
+                            trimmed
+                        ),
+                            tag,
+                        ))
+                    }
+                }
+            }
+        }
+    }
+    Ok((nodes, None))
+}
+
+fn unclosed_error<'t>(
+    opening: &str,
+    expected: &str,
+    tag: &'t str,
+    found: Option<(&'t str, &'t str)>,
+) -> StepError<'t> {
+    let err = StepError::new(
+        format!(
+            "Expected a '{{% {} %}}' to close this '{}'",
+            expected, opening
+        ),
+        tag,
+    );
+    match found {
+        Some((keyword, found_tag)) => {
+            err.with_note(format!("Found a '{{% {} %}}' instead", keyword), found_tag)
+        }
+        None => err,
+    }
+}
+
+// 'name | filter | filter(key="value", "value")'
+fn parse_expr(source: &str) -> TemplateResult<'_, Expr<'_>> {
+    let mut segments = split_outside_quotes(source, '|')?.into_iter();
+    let path_source = segments.next().unwrap_or(source).trim();
+    let path = path_source.split('.').map(str::trim).collect::<Vec<_>>();
+    if let Some(name) = path.iter().find(|name| !is_identifier(name)) {
+        let span = if name.is_empty() { source } else { name };
+        return Err(StepError::new(
+            format!(
+                "Expected a variable name like 'entry.chord', not {:?}",
+                path_source
+            ),
+            span,
+        ));
+    }
+
+    let mut filters = Vec::new();
+    for segment in segments {
+        let segment = segment.trim();
+        let (name, args) = match segment.find('(') {
+            Some(i) if segment.ends_with(')') => (
+                segment[..i].trim(),
+                Some(&segment[i + 1..segment.len() - 1]),
+            ),
+            _ => (segment, None),
+        };
+        let mut parsed_args = Vec::new();
+        let args = args.filter(|args| !args.trim().is_empty());
+        for arg in args.map_or(Ok(Vec::new()), |args| split_outside_quotes(args, ','))? {
+            let arg = arg.trim();
+            let (key, value) = match arg.find('=') {
+                Some(i) if !arg[..i].contains('"') => (Some(arg[..i].trim()), arg[i + 1..].trim()),
+                _ => (None, arg),
+            };
+            // An empty value is pointed at with its 'key=' or the whole filter
+            let context = if arg.is_empty() { segment } else { arg };
+            parsed_args.push((key, string_literal(value, context)?));
+        }
+        let filter = Filter {
+            name,
+            args: parsed_args,
+        };
+        check_filter(&filter, segment)?;
+        filters.push(filter);
+    }
+    Ok(Expr { path, filters })
+}
+
+fn check_filter<'t>(filter: &Filter<'t>, span: &'t str) -> TemplateResult<'t, ()> {
+    let keyed = filter.args.iter().filter(|(key, _)| key.is_some()).count();
+    let positional = filter.args.len() - keyed;
+    let is_valid = match filter.name {
+        "upper" | "lower" | "quote" | "sh_quote" | "json" | "one_line" => filter.args.is_empty(),
+        "join" => keyed == 0 && positional <= 1,
+        "replace" => keyed == 0 && positional == 2,
+        "map" => positional == 0,
+        _ => {
+            return Err(StepError::new(
+                format!("Unknown filter {:?}. Expected one of 'upper', 'lower', 'map', 'join', 'replace', 'quote', 'sh_quote', 'json' or 'one_line'", filter.name),
+                span,
+            ))
+        }
+    };
+    if is_valid {
+        Ok(())
+    } else {
+        Err(StepError::new(
+            format!(
+                "Wrong arguments for '{}'. Expected {}",
+                filter.name,
+                match filter.name {
+                    "join" => "'join' or 'join(\"<separator>\")'",
+                    "replace" => "'replace(\"<from>\", \"<to>\")'",
+                    "map" => "'map(<from>=\"<to>\", ...)'",
+                    _ => "no arguments",
+                }
+            ),
+            span,
+        ))
+    }
+}
+
+// Only '"..."' with backslash escapes
+fn string_literal<'t>(source: &'t str, context: &'t str) -> TemplateResult<'t, String> {
+    let inside = match source.strip_prefix('"').and_then(|s| s.strip_suffix('"')) {
+        Some(inside) if source.len() >= 2 => inside,
+        _ => {
+            let span = if source.is_empty() { context } else { source };
+            return Err(StepError::new(
+                "Expected a string in double quotes like \"value\"",
+                span,
+            ));
+        }
+    };
+    let mut value = String::with_capacity(inside.len());
+    let mut chars = inside.chars();
+    while let Some(ch) = chars.next() {
+        match (ch, ch == '\\') {
+            (_, true) => match chars.next() {
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some(c) => value.push(c),
+                None => value.push('\\'),
+            },
+            (c, false) => value.push(c),
+        }
+    }
+    Ok(value)
+}
+
+fn split_outside_quotes(source: &str, delimiter: char) -> TemplateResult<'_, Vec<&str>> {
+    let mut segments = Vec::new();
+    let mut start = 0;
+    let mut is_quoted = false;
+    let mut is_escaped = false;
+    for (i, ch) in source.char_indices() {
+        match ch {
+            _ if is_escaped => is_escaped = false,
+            '\\' if is_quoted => is_escaped = true,
+            '"' => is_quoted = !is_quoted,
+            c if c == delimiter && !is_quoted => {
+                segments.push(&source[start..i]);
+                start = i + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    if is_quoted {
+        return Err(StepError::new("Unclosed double quote", source));
+    }
+    segments.push(&source[start..]);
+    Ok(segments)
+}
+
+fn strip_word<'t>(source: &'t str, word: &str) -> Option<&'t str> {
+    let rest = source.strip_prefix(word)?;
+    match rest.chars().next() {
+        Some(c) if c.is_whitespace() => Some(rest.trim_start()),
+        _ => None,
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/******************************************************************************
+ * Rendering
+ ******************************************************************************/
+#[derive(Clone)]
+enum Value<'x> {
+    Str(String),
+    Bool(bool),
+    List(Vec<Value<'x>>),
+    Chord(&'x Chord),
+    Keyspace(&'x Keyspace<'x, 'x>),
+    Entry(&'x Chord, &'x KeyspaceAction<'x, 'x>),
+    Shortcut(&'x Shortcut<'x, 'x>),
+    Loop(usize, usize), // (index, length)
+}
+
+type Scope<'t, 'x> = Vec<(&'t str, Value<'x>)>;
+
+impl<'t> Template<'t> {
+    pub fn render<'x>(
+        &self,
+        shortcuts: &'x [Shortcut<'x, 'x>],
+        keyspaces: &'x [Keyspace<'x, 'x>],
+    ) -> TemplateResult<'t, String> {
+        let mut scope = vec![
+            (
+                "keyspaces",
+                Value::List(keyspaces.iter().map(Value::Keyspace).collect()),
+            ),
+            (
+                "shortcuts",
+                Value::List(shortcuts.iter().map(Value::Shortcut).collect()),
+            ),
+        ];
+        let mut output = String::new();
+        render_nodes(&self.0, &mut scope, &mut output)?;
+        Ok(output)
+    }
+}
+
+fn render_nodes<'t, 'x>(
+    nodes: &[Node<'t>],
+    scope: &mut Scope<'t, 'x>,
+    output: &mut String,
+) -> TemplateResult<'t, ()> {
+    for node in nodes {
+        match node {
+            Node::Text(text) => output.push_str(text),
+            Node::Output(expr) => {
+                let value = evaluate(expr, scope)?;
+                output.push_str(&text(&value).map_err(|msg| error_at_path(msg, expr))?);
+            }
+            Node::For { name, list, body } => {
+                let items = match evaluate(list, scope)? {
+                    Value::List(items) => items,
+                    _ => return Err(error_at_path("Only a list can be looped over".into(), list)),
+                };
+                let len = items.len();
+                for (i, item) in items.into_iter().enumerate() {
+                    scope.push(("loop", Value::Loop(i, len)));
+                    scope.push((name, item));
+                    render_nodes(body, scope, output)?;
+                    scope.truncate(scope.len() - 2);
+                }
+            }
+            Node::If {
+                negate,
+                condition,
+                then,
+                otherwise,
+            } => {
+                let is_true = match evaluate(condition, scope)? {
+                    Value::Bool(b) => b,
+                    Value::Str(s) => !s.is_empty(),
+                    Value::List(items) => !items.is_empty(),
+                    _ => true,
+                };
+                if is_true != *negate {
+                    render_nodes(then, scope, output)?;
+                } else {
+                    render_nodes(otherwise, scope, output)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn error_at_path<'t>(message: String, expr: &Expr<'t>) -> StepError<'t> {
+    StepError::new(message, expr.path[expr.path.len() - 1])
+}
+
+fn evaluate<'t, 'x>(expr: &Expr<'t>, scope: &Scope<'t, 'x>) -> TemplateResult<'t, Value<'x>> {
+    let name = expr.path[0];
+    let mut value = match scope.iter().rev().find(|(other, _)| *other == name) {
+        Some((_, value)) => value.clone(),
+        None => {
+            let names = scope.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+            return Err(StepError::new(
+                format!("Unknown variable {:?}. Expected one of {:?}", name, names),
+                name,
+            ));
+        }
+    };
+    for field_name in &expr.path[1..] {
+        value = field(&value, field_name).ok_or_else(|| {
+            StepError::new(
+                format!("There is no field {:?} here", field_name),
+                field_name,
+            )
+        })?;
+    }
+    for filter in &expr.filters {
+        value = apply(value, filter).map_err(|msg| StepError::new(msg, filter.name))?;
+    }
+    Ok(value)
+}
+
+fn chord_list(chords: &[Chord]) -> Value<'_> {
+    Value::List(chords.iter().map(Value::Chord).collect())
+}
+
+fn field<'x>(value: &Value<'x>, name: &str) -> Option<Value<'x>> {
+    Some(match (value, name) {
+        (Value::Keyspace(keyspace), "title") => chord_list(keyspace.title.0),
+        (Value::Keyspace(keyspace), "name") => Value::Str(identifier(keyspace.title.0)),
+        (Value::Keyspace(keyspace), "is_root") => Value::Bool(keyspace.title.0.is_empty()),
+        (Value::Keyspace(keyspace), "entries") => Value::List(
+            keyspace
+                .list
+                .iter()
+                .map(|(chord, action)| Value::Entry(chord, action))
+                .collect(),
+        ),
+
+        (Value::Entry(chord, _), "chord") => Value::Chord(chord),
+        (Value::Entry(_, action), "is_state") => {
+            Value::Bool(matches!(action, KeyspaceAction::SetState(_)))
+        }
+        (Value::Entry(_, action), "is_action") => {
+            Value::Bool(matches!(action, KeyspaceAction::Action(_)))
+        }
+        // The name and title of the keyspace that a 'SetState' enters
+        (Value::Entry(_, action), "next") => Value::Str(match action {
+            KeyspaceAction::SetState(chord_list) => identifier(chord_list),
+            KeyspaceAction::Action(_) => String::new(),
+        }),
+        (Value::Entry(_, action), "next_title") => match action {
+            KeyspaceAction::SetState(chords) => chord_list(chords),
+            KeyspaceAction::Action(_) => Value::List(Vec::new()),
+        },
+        (Value::Entry(_, action), "action") => Value::Str(match action {
+            KeyspaceAction::SetState(_) => String::new(),
+            KeyspaceAction::Action(action) => join_action(action),
+        }),
+
+        (Value::Chord(chord), "key") => Value::Str(chord.key.as_str().into()),
+        (Value::Chord(chord), "modifiers") => Value::List(
+            chord
+                .modifier_list()
+                .map(|modifier| Value::Str(format!("{:?}", modifier).to_lowercase()))
+                .collect(),
+        ),
+
+        (Value::Shortcut(shortcut), "hotkey") => chord_list(shortcut.hotkey.0),
+        (Value::Shortcut(shortcut), "action") => Value::Str(join_action(shortcut.action)),
+
+        (Value::Loop(i, _), "index") => Value::Str((i + 1).to_string()),
+        (Value::Loop(i, _), "first") => Value::Bool(*i == 0),
+        (Value::Loop(i, len), "last") => Value::Bool(i + 1 == *len),
+
+        (Value::List(items), "len") => Value::Str(items.len().to_string()),
+        _ => return None,
+    })
+}
+
+fn text(value: &Value) -> Result<String, String> {
+    match value {
+        Value::Str(s) => Ok(s.clone()),
+        Value::Bool(b) => Ok(b.to_string()),
+        Value::Chord(chord) => Ok(chord.to_string()),
+        // Displayed like 'Hotkey' when it is a list of chords
+        Value::List(items) => {
+            let is_chords = items.iter().all(|item| matches!(item, Value::Chord(_)));
+            let texts = items.iter().map(text).collect::<Result<Vec<_>, _>>()?;
+            Ok(texts.join(if is_chords { " ; " } else { " " }))
+        }
+        Value::Keyspace(_) => Err("A keyspace cannot be output, use one of its fields".into()),
+        Value::Entry(..) => Err("An entry cannot be output, use one of its fields".into()),
+        Value::Shortcut(_) => Err("A shortcut cannot be output, use one of its fields".into()),
+        Value::Loop(..) => Err("'loop' cannot be output, use one of its fields".into()),
+    }
+}
+
+// Applied to each item of a list, except for 'join'
+fn apply<'x>(value: Value<'x>, filter: &Filter) -> Result<Value<'x>, String> {
+    let positional = |i: usize| filter.args[i].1.as_str();
+    let convert = |s: String| -> String {
+        match filter.name {
+            "upper" => s.to_uppercase(),
+            "lower" => s.to_lowercase(),
+            "quote" => quote(&s),
+            "sh_quote" => sh_quote(&s),
+            "json" => json_string(&s),
+            "one_line" => shell_one_line(&s).into_owned(),
+            "replace" => s.replace(positional(0), positional(1)),
+            "map" => filter
+                .args
+                .iter()
+                .find(|(key, _)| *key == Some(s.as_str()))
+                .map_or(s, |(_, to)| to.clone()),
+            _ => unreachable!("DEV: Filters are checked when parsing"),
+        }
+    };
+    match (filter.name, value) {
+        ("join", Value::List(items)) => {
+            let texts = items.iter().map(text).collect::<Result<Vec<_>, _>>()?;
+            let separator = filter.args.first().map_or("", |(_, s)| s.as_str());
+            Ok(Value::Str(texts.join(separator)))
+        }
+        ("join", _) => Err("Only a list can be joined".into()),
+        (_, Value::List(items)) => items
+            .iter()
+            .map(|item| text(item).map(|s| Value::Str(convert(s))))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Value::List),
+        (_, value) => Ok(Value::Str(convert(text(&value)?))),
+    }
+}
+
+#[test]
+fn template_i3() {
+    use crate::keyspace::KeyspaceList;
+    use crate::parser::{parse_into_shortcut_list, validate_and_calculate_allocations};
+    let source = r#"
+|super Space; super w| nmtui && echo "done"
+|alt shift Return| printf '%s\t\n' "a	b"
+"#;
+    let mut errors = Vec::new();
+    let metadata = validate_and_calculate_allocations(source, &mut errors);
+    let generator = parse_into_shortcut_list(metadata, &mut errors);
+    let shortcut_list = generator.allocate_shortcut_list().unwrap();
+    let keyspace_list = generator.allocate_keyspace_list().unwrap();
+
+    let template = Template::parse(include_str!("../i3.template")).unwrap();
+    assert_eq!(
+        template.render(&shortcut_list, &keyspace_list).unwrap(),
+        r#"# Generated from a template
+bindsym Mod4+space mode "super_space"
+bindsym Shift+Mod1+Return exec --no-startup-id "printf '%s\\t\\n' \"a	b\""
+
+mode "super_space" {
+  bindsym Mod4+w exec --no-startup-id "nmtui && echo \"done\""; mode "default"
+  bindsym Escape mode "default"
+}
+"#
+    );
+
+    let template =
+        Template::parse("{% raw %}\n{{ x }} {% endfor %}\n{% endraw %}\n{{ keyspaces.len }}");
+    assert_eq!(
+        template
+            .unwrap()
+            .render(&shortcut_list, &keyspace_list)
+            .unwrap(),
+        "{{ x }} {% endfor %}\n2"
+    );
+    let err = Template::parse("{% raw %}{{ x }}").err();
+    assert_eq!(err.map(|err| err.span), Some("{% raw %}"));
+
+    let err = Template::parse("{% for x in keyspaces %}{{ x.name }}").err();
+    assert_eq!(
+        err.map(|err| err.message),
+        Some("Expected a '{% endfor %}' to close this 'for'".into())
+    );
+    let err = Template::parse("{{ keyspaces | join | nope }}").err();
+    assert_eq!(err.map(|err| err.span), Some("nope"));
+    let source = "{{ keyspaces | map(a=) }}";
+    let err = Template::parse(source).err().unwrap();
+    assert_eq!(err.span, "a=");
+    assert!(err
+        .render(std::path::Path::new("t"), source)
+        .contains("Expected a string in double quotes"));
+}